@@ -0,0 +1,5 @@
+//! Path tracer following the Ray Tracing in One Weekend books: build a
+//! `HittableList` of objects and lights, then `Camera::render` it into a
+//! `Film` and save that as an image.
+pub mod raytracer;
+pub mod vec3;
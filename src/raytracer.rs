@@ -1,7 +1,18 @@
 pub(crate) mod camera;
-pub(crate) mod hittable;
+pub(crate) mod film;
+pub(crate) mod filter;
+pub mod hittable;
 pub(crate) mod ray;
+pub(crate) mod scene;
+pub(crate) mod spectrum;
 
 pub use camera::Camera;
-pub use hittable::{HitRecord, Hittable, HittableList, Sphere};
+pub use film::Film;
+pub use filter::Filter;
+pub use hittable::{
+    AaRect, Animated, BvhNode, Cone, Csg, Curve, Curves, Cylinder, Disk, Heightfield, HitRecord,
+    Hittable, HittableList, Interval, Light, LightList, Mesh, MotionPath, Perturbed, Plane, Quad,
+    RayMarched, Sdf, Sided, SignedDistance, Sphere, Torus, Transform, TriangleMesh,
+};
 pub use ray::Ray;
+pub use scene::{Background, Scene};
@@ -1,7 +1,10 @@
+use super::film::Film;
+use super::filter::Filter;
 use super::ray::Ray;
-use crate::raytracer::Hittable;
-use crate::vec3::{Color, Point3, Vec3};
-use rand::rngs::ThreadRng;
+use super::spectrum;
+use crate::raytracer::Scene;
+use crate::vec3::{Point3, Vec3};
+use rand::RngCore;
 use rand::{thread_rng, Rng};
 use rayon::prelude::*;
 use std::ops::Range;
@@ -19,9 +22,13 @@ pub struct Camera {
     v: Vec3,
     // w: Vec3,
     lens_radius: f64,
+    filter: Filter,
+    // Trace one wavelength per path instead of RGB
+    spectral: bool,
 }
 
 impl Camera {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         lookfrom: Point3,
         lookat: Point3,
@@ -60,9 +67,17 @@ impl Camera {
             v,
             // w,
             lens_radius,
+            filter: Filter::default(),
+            spectral: false,
         }
     }
-    pub fn get_ray(&self, u: f64, v: f64, rng: &mut ThreadRng, time_frame: &Range<f64>) -> Ray {
+    pub fn set_filter(&mut self, filter: Filter) {
+        self.filter = filter;
+    }
+    pub fn set_spectral(&mut self, spectral: bool) {
+        self.spectral = spectral;
+    }
+    pub fn get_ray(&self, u: f64, v: f64, rng: &mut dyn RngCore, time_frame: &Range<f64>) -> Ray {
         let rd = self.lens_radius * Vec3::rand_in_disk(rng);
         let offset = self.u * rd.x + self.v * rd.y;
 
@@ -72,16 +87,8 @@ impl Camera {
             rng.gen_range(time_frame.clone()),
         )
     }
-    pub fn render(
-        &self,
-        za_warudo: &(dyn Hittable + Sync),
-        samples_per_pixel: u16,
-        max_depht: u16,
-        time_frame: Range<f64>,
-    ) -> String {
-        let mut render = Vec::new();
-        render.push(format!("P3\n{} {}\n255", self.img_width, self.img_height));
-
+    pub fn render(&self, scene: &Scene, samples_per_pixel: u16, max_depht: u16) -> Film {
+        let time_frame = scene.time_frame().0..scene.time_frame().1;
         // let mut rng = rand::thread_rng();
 
         // for j in (0..self.img_height).rev() {
@@ -101,25 +108,37 @@ impl Camera {
         // }
         // render.join("\n")
         let done = Arc::new(Mutex::new(0_u32));
+        // Rows of neighboring pixels a sample can be splatted into
+        let reach = self.filter.radius().ceil() as i64;
 
-        let image: String = (0..self.img_width * self.img_height)
+        let bands: Vec<Film> = (0..self.img_height)
             .into_par_iter()
-            .map_init(thread_rng, |rng, screen_pos| {
-                let mut pixel_color = Color::zeros();
-                let i = screen_pos % self.img_width;
-                let j = self.img_height - 1 - screen_pos / self.img_width;
-                for _ in 0..samples_per_pixel {
-                    let u = (i as f64 + rng.gen_range(0.0..1.0)) / (self.img_width - 1) as f64;
-                    let v = (j as f64 + rng.gen_range(0.0..1.0)) / (self.img_height - 1) as f64;
-
-                    let r = self.get_ray(u, v, rng, &time_frame);
-                    pixel_color += r.color(rng, za_warudo, max_depht);
+            .map_init(thread_rng, |rng, j| {
+                let mut band =
+                    Film::new_band(self.img_width, j as i64 - reach, 2 * reach as u32 + 1);
+                for i in 0..self.img_width {
+                    for s in 0..samples_per_pixel {
+                        let x = i as f64 + rng.gen_range(0.0..1.0);
+                        let y = j as f64 + rng.gen_range(0.0..1.0);
+                        let u = x / (self.img_width - 1) as f64;
+                        let v = y / (self.img_height - 1) as f64;
+
+                        let r = self.get_ray(u, v, rng, &time_frame);
+                        let color = if self.spectral {
+                            let lambda = spectrum::sample_wavelength(s, samples_per_pixel, rng);
+                            r.with_wavelength(lambda).color(rng, scene, max_depht)
+                                * spectrum::wavelength_weight(lambda)
+                        } else {
+                            r.color(rng, scene, max_depht)
+                        };
+                        band.add_sample(x, y, color, &self.filter);
+                    }
                 }
 
                 {
                     // Display progress
                     let mut lock = done.lock().unwrap();
-                    *lock += 1;
+                    *lock += self.img_width;
                     print!(
                         "\rPixels done: {:>10}/{:<10} = {:>6.2}%",
                         *lock,
@@ -128,11 +147,14 @@ impl Camera {
                     );
                 }
 
-                pixel_color.write(samples_per_pixel) + "\n"
+                band
             })
-            .collect::<String>();
+            .collect();
 
-        render.push(image);
-        render.join("\n")
+        let mut film = Film::new(self.img_width, self.img_height);
+        for band in &bands {
+            film.merge(band);
+        }
+        film
     }
 }
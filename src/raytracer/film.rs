@@ -0,0 +1,159 @@
+use super::filter::Filter;
+use crate::vec3::Color;
+use std::{fs, io, path::Path};
+
+// Weight sums below this are left black. Filters with negative lobes can
+// nearly cancel out over sparse samples, and dividing by what is left would
+// blow the pixel up.
+const MIN_WEIGHT: f64 = 1e-3;
+
+// Weighted pixel accumulator covering the rows [y0, y0 + height) of the image.
+// Rows are counted from the bottom of the image, like the camera's `v`.
+pub struct Film {
+    width: u32,
+    y0: i64,
+    height: u32,
+    pixels: Vec<Color>,
+    weights: Vec<f64>,
+}
+
+impl Film {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self::new_band(width, 0, height)
+    }
+    pub fn new_band(width: u32, y0: i64, height: u32) -> Self {
+        let size = (width * height) as usize;
+        Film {
+            width,
+            y0,
+            height,
+            pixels: vec![Color::zeros(); size],
+            weights: vec![0.0; size],
+        }
+    }
+    // (x, y) is the sample position in pixel units, pixel (i, j) covering [i, i+1) x [j, j+1)
+    pub fn add_sample(&mut self, x: f64, y: f64, color: Color, filter: &Filter) {
+        let radius = filter.radius();
+        let i_min = ((x - 0.5 - radius).ceil() as i64).max(0);
+        let i_max = ((x - 0.5 + radius).floor() as i64).min(self.width as i64 - 1);
+        let j_min = ((y - 0.5 - radius).ceil() as i64).max(self.y0);
+        let j_max = ((y - 0.5 + radius).floor() as i64).min(self.y0 + self.height as i64 - 1);
+
+        for j in j_min..=j_max {
+            for i in i_min..=i_max {
+                let weight = filter.evaluate(i as f64 + 0.5 - x, j as f64 + 0.5 - y);
+                if weight == 0.0 {
+                    continue;
+                }
+                let index = ((j - self.y0) * self.width as i64 + i) as usize;
+                self.pixels[index] += weight * color;
+                self.weights[index] += weight;
+            }
+        }
+    }
+    pub fn merge(&mut self, band: &Film) {
+        for row in 0..band.height as i64 {
+            let j = band.y0 + row;
+            if j < self.y0 || j >= self.y0 + self.height as i64 {
+                continue;
+            }
+            for i in 0..self.width.min(band.width) as i64 {
+                let src = (row * band.width as i64 + i) as usize;
+                let dst = ((j - self.y0) * self.width as i64 + i) as usize;
+                self.pixels[dst] += band.pixels[src];
+                self.weights[dst] += band.weights[src];
+            }
+        }
+    }
+    pub fn pixel(&self, i: u32, j: u32) -> Color {
+        let index = ((j as i64 - self.y0) * self.width as i64 + i as i64) as usize;
+        let weight = self.weights[index];
+        if weight < MIN_WEIGHT {
+            return Color::zeros();
+        }
+        // Negative lobes may also leave channels below zero
+        let color = self.pixels[index] / weight;
+        Color::new(color.x.max(0.0), color.y.max(0.0), color.z.max(0.0))
+    }
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    // Plain PPM image, top row first
+    pub fn ppm(&self) -> String {
+        let mut lines = Vec::with_capacity(self.pixels.len() + 1);
+        lines.push(format!("P3\n{} {}\n255\n", self.width, self.height));
+        for j in (0..self.height).rev() {
+            for i in 0..self.width {
+                lines.push(self.pixel(i, self.y0 as u32 + j).write(1) + "\n");
+            }
+        }
+        lines.concat()
+    }
+    pub fn save_ppm(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.ppm())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Film;
+    use crate::{raytracer::filter::Filter, vec3::Color};
+
+    #[test]
+    fn box_filter_splats_into_one_pixel() {
+        let mut film = Film::new(2, 2);
+        film.add_sample(0.3, 1.7, Color::ones(), &Filter::default());
+        assert_eq!(film.pixel(0, 1), Color::ones());
+        assert_eq!(film.pixel(1, 1), Color::zeros());
+        assert_eq!(film.pixel(0, 0), Color::zeros());
+    }
+
+    #[test]
+    fn tent_filter_splats_into_neighbors() {
+        let mut film = Film::new(3, 1);
+        film.add_sample(1.5, 0.5, Color::ones(), &Filter::tent(1.5));
+        assert_eq!(film.pixel(0, 0), Color::ones());
+        assert_eq!(film.pixel(1, 0), Color::ones());
+        assert_eq!(film.pixel(2, 0), Color::ones());
+    }
+
+    #[test]
+    fn mitchell_filter_stays_non_negative() {
+        let filter = Filter::mitchell(2.0);
+        // Only the negative lobe of a sample reaches the second pixel
+        let mut film = Film::new(2, 1);
+        film.add_sample(0.0, 0.5, Color::ones(), &filter);
+        assert_eq!(film.pixel(0, 0), Color::ones());
+        assert_eq!(film.pixel(1, 0), Color::zeros());
+
+        // A bright sample next door outweighs a dim one right on the pixel
+        film.add_sample(1.5, 0.5, 0.1 * Color::ones(), &filter);
+        film.add_sample(0.0, 0.5, 10.0 * Color::ones(), &filter);
+        assert_eq!(film.pixel(1, 0), Color::zeros());
+        assert_eq!(film.ppm(), "P3\n2 1\n255\n255 255 255\n0 0 0\n");
+    }
+
+    #[test]
+    fn merge_band() {
+        let filter = Filter::tent(1.0);
+        let mut film = Film::new(2, 3);
+        let mut band = Film::new_band(2, 1, 1);
+        band.add_sample(0.5, 1.5, Color::ones(), &filter);
+        film.merge(&band);
+        assert_eq!(film.pixel(0, 1), Color::ones());
+        assert_eq!(film.pixel(0, 0), Color::zeros());
+    }
+
+    #[test]
+    fn ppm() {
+        let mut film = Film::new(2, 2);
+        film.add_sample(0.5, 1.5, Color::ones(), &Filter::default());
+        assert_eq!(
+            film.ppm(),
+            "P3\n2 2\n255\n255 255 255\n0 0 0\n0 0 0\n0 0 0\n"
+        );
+    }
+}
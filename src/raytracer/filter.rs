@@ -0,0 +1,119 @@
+// Pixel reconstruction filters
+//
+// Every sample is splatted into all the pixels whose center lies within the
+// filter radius, weighted by the filter evaluated at the offset between the
+// sample and the pixel center (in pixel units).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Filter {
+    Box { radius: f64 },
+    Tent { radius: f64 },
+    Gaussian { radius: f64, alpha: f64 },
+    Mitchell { radius: f64, b: f64, c: f64 },
+}
+
+impl Filter {
+    pub fn box_filter(radius: f64) -> Self {
+        Filter::Box { radius }
+    }
+    pub fn tent(radius: f64) -> Self {
+        Filter::Tent { radius }
+    }
+    pub fn gaussian(radius: f64, alpha: f64) -> Self {
+        Filter::Gaussian { radius, alpha }
+    }
+    pub fn mitchell(radius: f64) -> Self {
+        // Mitchell & Netravali's recommended B = C = 1/3
+        Filter::Mitchell {
+            radius,
+            b: 1. / 3.,
+            c: 1. / 3.,
+        }
+    }
+    pub fn radius(&self) -> f64 {
+        match *self {
+            Filter::Box { radius }
+            | Filter::Tent { radius }
+            | Filter::Gaussian { radius, .. }
+            | Filter::Mitchell { radius, .. } => radius,
+        }
+    }
+    pub fn evaluate(&self, dx: f64, dy: f64) -> f64 {
+        self.evaluate_1d(dx) * self.evaluate_1d(dy)
+    }
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        let x = x.abs();
+        match *self {
+            Filter::Box { radius } => {
+                if x <= radius {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Filter::Tent { radius } => (radius - x).max(0.0),
+            Filter::Gaussian { radius, alpha } => {
+                ((-alpha * x * x).exp() - (-alpha * radius * radius).exp()).max(0.0)
+            }
+            Filter::Mitchell { radius, b, c } => {
+                if x > radius {
+                    return 0.0;
+                }
+                // The cubic is defined on [0, 2]
+                let x = 2.0 * x / radius;
+                if x < 1.0 {
+                    ((12. - 9. * b - 6. * c) * x * x * x
+                        + (-18. + 12. * b + 6. * c) * x * x
+                        + (6. - 2. * b))
+                        / 6.
+                } else {
+                    ((-b - 6. * c) * x * x * x
+                        + (6. * b + 30. * c) * x * x
+                        + (-12. * b - 48. * c) * x
+                        + (8. * b + 24. * c))
+                        / 6.
+                }
+            }
+        }
+    }
+}
+
+impl Default for Filter {
+    // Same as splatting every sample into the pixel it lands in
+    fn default() -> Self {
+        Filter::box_filter(0.5)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Filter;
+
+    #[test]
+    fn box_filter() {
+        let filter = Filter::default();
+        assert_eq!(filter.evaluate(0.0, 0.0), 1.0);
+        assert_eq!(filter.evaluate(0.4, -0.4), 1.0);
+        assert_eq!(filter.evaluate(0.6, 0.0), 0.0);
+    }
+
+    #[test]
+    fn filters_vanish_at_radius() {
+        for filter in [
+            Filter::tent(1.0),
+            Filter::gaussian(1.5, 2.0),
+            Filter::mitchell(2.0),
+        ] {
+            let r = filter.radius();
+            assert!(filter.evaluate(0.0, 0.0) > 0.0);
+            assert!(filter.evaluate(r, 0.0).abs() < 1e-12);
+            assert_eq!(filter.evaluate(r + 0.1, 0.0), 0.0);
+            assert_eq!(filter.evaluate(0.3, -0.2), filter.evaluate(-0.3, 0.2));
+        }
+    }
+
+    #[test]
+    fn mitchell_negative_lobe() {
+        let filter = Filter::mitchell(2.0);
+        assert!(filter.evaluate(1.5, 0.0) < 0.0);
+    }
+}
@@ -1,36 +1,149 @@
 pub(crate) mod aabb;
+pub(crate) mod aarect;
+pub(crate) mod animated;
+pub(crate) mod bezier;
 pub(crate) mod bvh;
-pub(crate) mod material;
+pub(crate) mod cone;
+pub(crate) mod csg;
+pub(crate) mod curves;
+pub(crate) mod cylinder;
+pub(crate) mod disk;
+pub(crate) mod heightfield;
+pub(crate) mod keyframes;
+pub(crate) mod light;
+pub mod material;
+pub(crate) mod mesh;
+pub(crate) mod perturbed;
+pub(crate) mod plane;
+pub(crate) mod quad;
+pub(crate) mod sdf;
+pub(crate) mod sided;
 pub(crate) mod sphere;
+pub mod texture;
+pub(crate) mod torus;
 use crate::raytracer::Ray;
 use crate::vec3::{Point3, Vec3};
 pub use aabb::Aabb;
+pub use aarect::AaRect;
+pub use animated::{Animated, Transform};
+pub use bvh::BvhNode;
+pub use cone::Cone;
+pub use csg::Csg;
+pub use curves::{Curve, Curves};
+pub use cylinder::Cylinder;
+pub use disk::Disk;
+pub use heightfield::Heightfield;
+pub use light::{Light, LightList};
 use material::Material;
-pub use sphere::Sphere;
+pub use mesh::{Mesh, TriangleMesh};
+pub use perturbed::Perturbed;
+pub use plane::Plane;
+pub use quad::Quad;
+pub use sdf::{RayMarched, Sdf, SignedDistance};
+pub use sided::Sided;
+pub use sphere::{MotionPath, Sphere};
 use std::sync::Arc;
 use std::vec::Vec;
+pub use torus::Torus;
 
 pub trait Hittable {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool;
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool;
     fn bounding_box(&self, time_frame: (f64, f64)) -> Option<Aabb>;
+    // Stretches of the whole line of `r` spent inside the object, in order,
+    // for constructive solid geometry. Crossing a front face enters the
+    // object and a back face leaves it, so a lone plane bounds a half-space.
+    fn hit_intervals<'a>(&'a self, r: &Ray) -> Vec<Interval<'a>> {
+        let mut intervals = Vec::new();
+        // Overlapping parts, like the members of a list, nest their faces
+        let mut depth = 0;
+        let mut enter = HitRecord::new();
+        let mut t_min = f64::NEG_INFINITY;
+        loop {
+            let mut rec = HitRecord::new();
+            if !self.hit(r, t_min, f64::INFINITY, &mut rec) {
+                break;
+            }
+            t_min = rec.t.next_up();
+            if rec.front_face {
+                if depth == 0 {
+                    enter = rec;
+                }
+                depth += 1;
+            } else if depth == 0 {
+                // Leaving without having entered: the line starts inside
+                let enter = HitRecord {
+                    t: f64::NEG_INFINITY,
+                    ..rec
+                };
+                intervals.push(Interval { enter, exit: rec });
+            } else {
+                depth -= 1;
+                if depth == 0 {
+                    intervals.push(Interval { enter, exit: rec });
+                }
+            }
+        }
+        if depth > 0 {
+            let exit = HitRecord {
+                t: f64::INFINITY,
+                ..enter
+            };
+            intervals.push(Interval { enter, exit });
+        }
+        intervals
+    }
+}
+
+// Solid angle density along `r` of points picked uniformly over a flat
+// emitter of `area`: distance² / (cosine · area) where the ray hits it
+pub(crate) fn planar_pdf_value(emitter: &dyn Hittable, area: f64, r: &Ray) -> f64 {
+    let mut rec = HitRecord::new();
+    if !emitter.hit(r, 0.001, f64::INFINITY, &mut rec) {
+        return 0.0;
+    }
+    let distance_squared = rec.t * rec.t * r.direction.length_squared();
+    let cosine = (r.direction.dot(rec.normal) / r.direction.length()).abs();
+    distance_squared / (cosine * area)
+}
+
+// Part of a ray inside an object, between the hits entering and leaving it.
+// Either end may be infinite for unbounded objects.
+#[derive(Clone, Copy)]
+pub struct Interval<'a> {
+    pub enter: HitRecord<'a>,
+    pub exit: HitRecord<'a>,
 }
 
-pub struct HitRecord {
+#[derive(Clone, Copy)]
+pub struct HitRecord<'a> {
     pub p: Point3,
     pub normal: Vec3,
-    pub material: Material,
+    pub material: &'a Material,
     pub t: f64,
+    // Surface coordinates, for textures
+    pub u: f64,
+    pub v: f64,
+    // Derivatives of the hit point along u and v, for normal mapping
+    pub dpdu: Vec3,
+    pub dpdv: Vec3,
     pub front_face: bool,
+    // Wavelength of the ray that hit, in spectral mode
+    pub wavelength: Option<f64>,
 }
 
-impl HitRecord {
+impl HitRecord<'_> {
     pub fn new() -> Self {
         HitRecord {
             p: Point3::zeros(),
             normal: Vec3::zeros(),
             t: 0.0,
+            u: 0.0,
+            v: 0.0,
+            dpdu: Vec3::zeros(),
+            dpdv: Vec3::zeros(),
             front_face: false,
-            material: Material::None,
+            wavelength: None,
+            material: &material::NONE,
         }
     }
 
@@ -44,6 +157,13 @@ impl HitRecord {
     }
 }
 
+impl Default for HitRecord<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Default, Clone)]
 pub struct HittableList {
     pub objects: Vec<Arc<dyn Hittable + Send + Sync>>,
 }
@@ -62,8 +182,8 @@ impl HittableList {
     }
 }
 impl Hittable for HittableList {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
-        let mut temp_rec = HitRecord { ..*rec };
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        let mut temp_rec = *rec;
         let mut hit_any = false;
         let mut closest = t_max;
 
@@ -71,7 +191,7 @@ impl Hittable for HittableList {
             if obj.hit(r, t_min, closest, &mut temp_rec) {
                 hit_any = true;
                 closest = temp_rec.t;
-                *rec = HitRecord { ..temp_rec };
+                *rec = temp_rec;
             }
         }
         hit_any
@@ -81,7 +201,7 @@ impl Hittable for HittableList {
             return Option::None;
         }
 
-        let mut output_box: Aabb = self.objects[0].bounding_box(time_frame).unwrap();
+        let mut output_box: Aabb = self.objects[0].bounding_box(time_frame)?;
 
         for obj in &self.objects[1..] {
             if let Some(temp_box) = obj.bounding_box(time_frame) {
@@ -99,11 +219,16 @@ impl Hittable for HittableList {
 mod test {
     use std::sync::Arc;
 
-    use super::{HitRecord, Hittable, HittableList, Sphere};
+    use super::{
+        material::Material, BvhNode, Disk, HitRecord, Hittable, HittableList, Light, Plane, Quad,
+        Sphere,
+    };
     use crate::{
         raytracer::{hittable::Aabb, Ray},
         vec3::{Point3, Vec3},
     };
+    use rand::thread_rng;
+    use std::f64::consts::PI;
 
     #[test]
     fn set_face_normal() {
@@ -124,6 +249,22 @@ mod test {
         assert_eq!(rec.normal, -out_normal);
     }
 
+    #[test]
+    fn two_sided_hits() {
+        // Hits from inside are back faces, with the normal turned inwards
+        let sphere = Sphere::new(Point3::zeros(), 2.0, super::material::Material::None);
+        let mut rec = HitRecord::new();
+        let ray = Ray::new(Point3::zeros(), Vec3::new(1., 0., 0.), 0.0);
+        assert!(sphere.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert!(!rec.front_face);
+        assert_eq!(rec.normal, Vec3::new(-1., 0., 0.));
+
+        let ray = Ray::new(Point3::new(5., 0., 0.), Vec3::new(-1., 0., 0.), 0.0);
+        assert!(sphere.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert!(rec.front_face);
+        assert_eq!(rec.normal, Vec3::new(1., 0., 0.));
+    }
+
     #[test]
     fn hittable_list_empty_bounding_box() {
         let list = HittableList::new();
@@ -151,4 +292,61 @@ mod test {
             list.bounding_box((0.0, 0.0)).unwrap()
         );
     }
+
+    #[test]
+    fn unbounded_objects() {
+        let mut list = HittableList::new();
+        list.add(Arc::new(Plane::new(
+            Point3::zeros(),
+            Vec3::up(),
+            super::material::Material::None,
+        )));
+        list.add(Arc::new(Sphere::new(
+            Point3::new(0., 2., 0.),
+            1.0,
+            super::material::Material::None,
+        )));
+        assert_eq!(None, list.bounding_box((0.0, 0.0)));
+
+        // The hierarchy still finds both the sphere and the plane
+        let bvh = BvhNode::new(&list.objects, &mut thread_rng(), 0, 2, &(0.0, 0.0));
+        assert_eq!(None, bvh.bounding_box((0.0, 0.0)));
+        let mut rec = HitRecord::new();
+        let ray = Ray::new(Point3::new(0., 5., 0.), -Vec3::up(), 0.0);
+        assert!(bvh.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!(rec.t, 2.0);
+        let ray = Ray::new(Point3::new(3., 5., 0.), -Vec3::up(), 0.0);
+        assert!(bvh.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!(rec.t, 5.0);
+    }
+
+    #[test]
+    fn planar_pdf_matches_solid_angle() {
+        // The mean of 1 / pdf over sampled directions estimates the solid
+        // angle of a light: a sixth of the sphere for a face of a cube seen
+        // from its center, a cone for a disk seen along its axis
+        let quad = Quad::new(
+            Point3::new(-1., 1., -1.),
+            Vec3::new(2., 0., 0.),
+            Vec3::new(0., 0., 2.),
+            Material::None,
+        );
+        let disk = Disk::new(Point3::new(0., 3., 0.), -Vec3::up(), 1., Material::None);
+        let lights: [(&dyn Light, f64); 2] = [
+            (&quad, 4.0 * PI / 6.0),
+            (&disk, 2.0 * PI * (1.0 - 3.0 / 10f64.sqrt())),
+        ];
+        let mut rng = thread_rng();
+        let n = 10_000;
+        for (light, solid_angle) in lights {
+            let mut inverse_pdf = 0.0;
+            for _ in 0..n {
+                let direction = light.random(Point3::zeros(), 0.0, &mut rng);
+                let pdf = light.pdf_value(&Ray::new(Point3::zeros(), direction, 0.0));
+                assert!(pdf > 0.0);
+                inverse_pdf += 1.0 / pdf / n as f64;
+            }
+            assert!((inverse_pdf - solid_angle).abs() < 0.03 * solid_angle);
+        }
+    }
 }
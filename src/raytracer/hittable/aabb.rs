@@ -1,6 +1,6 @@
 use super::{HitRecord, Hittable};
 use crate::raytracer::Ray;
-use crate::vec3::Point3;
+use crate::vec3::{Point3, Vec3};
 
 // Axis-Aligned Bounding Box
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -13,6 +13,25 @@ impl Aabb {
     pub fn new(minimum: Point3, maximum: Point3) -> Self {
         Aabb { minimum, maximum }
     }
+    // Part of [t_min, t_max] along which the ray is inside the box
+    pub fn clip(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
+        let mut t_min = t_min;
+        let mut t_max = t_max;
+        for a in 0..3 {
+            let inv_d = 1.0 / r.direction[a];
+            let mut t0 = (self.minimum[a] - r.origin[a]) * inv_d;
+            let mut t1 = (self.maximum[a] - r.origin[a]) * inv_d;
+            if inv_d < 0.0 {
+                (t0, t1) = (t1, t0);
+            }
+            t_min = t0.max(t_min);
+            t_max = t1.min(t_max);
+            if t_max <= t_min {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
     pub fn surronding_box(box_a: &Aabb, box_b: &Aabb) -> Aabb {
         let minimum = Point3::new(
             box_a.minimum.x.min(box_b.minimum.x),
@@ -28,30 +47,24 @@ impl Aabb {
 
         Aabb { minimum, maximum }
     }
+    // Box grown by `margin` on every side
+    pub fn padded(&self, margin: f64) -> Aabb {
+        let padding = margin * Vec3::ones();
+        Aabb::new(self.minimum - padding, self.maximum + padding)
+    }
+    // Box padded so that flat shapes still get a non-zero volume
+    pub fn with_volume(&self) -> Aabb {
+        self.padded(0.0001)
+    }
 }
 
 impl Hittable for Aabb {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, _rec: &mut HitRecord) -> bool {
-        let mut t_min = t_min;
-        let mut t_max = t_max;
-        for a in 0..3 {
-            let inv_d = 1.0 / r.direction[a];
-            let mut t0 = (self.minimum[a] - r.origin[a]) * inv_d;
-            let mut t1 = (self.maximum[a] - r.origin[a]) * inv_d;
-            if inv_d < 0.0 {
-                (t0, t1) = (t1, t0);
-            }
-            t_min = t0.max(t_min);
-            t_max = t1.min(t_max);
-            if t_max <= t_min {
-                return false;
-            }
-        }
-        true
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, _rec: &mut HitRecord<'a>) -> bool {
+        self.clip(r, t_min, t_max).is_some()
     }
 
-    fn bounding_box(&self, time_frame: (f64, f64)) -> Option<Self> {
-        todo!()
+    fn bounding_box(&self, _time_frame: (f64, f64)) -> Option<Self> {
+        Some(*self)
     }
 }
 
@@ -0,0 +1,147 @@
+use super::{aabb::Aabb, light::Light, material::Material, planar_pdf_value, HitRecord, Hittable};
+use crate::{
+    raytracer::Ray,
+    vec3::{Point3, Vec3},
+};
+use rand::{Rng, RngCore};
+
+// Axis-aligned rectangle [a0, a1] x [b0, b1] lying in the plane `axis = k`
+pub struct AaRect {
+    axis: u64,
+    a0: f64,
+    a1: f64,
+    b0: f64,
+    b1: f64,
+    k: f64,
+    material: Material,
+}
+
+impl AaRect {
+    pub fn xy(x0: f64, x1: f64, y0: f64, y1: f64, k: f64, material: Material) -> Self {
+        Self::new(2, x0, x1, y0, y1, k, material)
+    }
+    pub fn xz(x0: f64, x1: f64, z0: f64, z1: f64, k: f64, material: Material) -> Self {
+        Self::new(1, x0, x1, z0, z1, k, material)
+    }
+    pub fn yz(y0: f64, y1: f64, z0: f64, z1: f64, k: f64, material: Material) -> Self {
+        Self::new(0, y0, y1, z0, z1, k, material)
+    }
+    #[allow(clippy::too_many_arguments)]
+    fn new(axis: u64, a0: f64, a1: f64, b0: f64, b1: f64, k: f64, material: Material) -> Self {
+        AaRect {
+            axis,
+            a0,
+            a1,
+            b0,
+            b1,
+            k,
+            material,
+        }
+    }
+    // In-plane axes, in increasing order
+    fn plane_axes(&self) -> (u64, u64) {
+        match self.axis {
+            0 => (1, 2),
+            1 => (0, 2),
+            _ => (0, 1),
+        }
+    }
+    fn point(&self, a: f64, b: f64, k: f64) -> Point3 {
+        match self.axis {
+            0 => Point3::new(k, a, b),
+            1 => Point3::new(a, k, b),
+            _ => Point3::new(a, b, k),
+        }
+    }
+    fn area(&self) -> f64 {
+        (self.a1 - self.a0) * (self.b1 - self.b0)
+    }
+}
+
+impl Hittable for AaRect {
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        let t = (self.k - r.origin[self.axis]) / r.direction[self.axis];
+        if !(t_min..t_max).contains(&t) {
+            return false;
+        }
+        let (axis_a, axis_b) = self.plane_axes();
+        let a = r.origin[axis_a] + t * r.direction[axis_a];
+        let b = r.origin[axis_b] + t * r.direction[axis_b];
+        if a < self.a0 || a > self.a1 || b < self.b0 || b > self.b1 {
+            return false;
+        }
+        let u = (a - self.a0) / (self.a1 - self.a0);
+        let v = (b - self.b0) / (self.b1 - self.b0);
+        let p = r.at(t);
+        if !self.material.is_opaque_at(r, t, u, v, p) {
+            return false;
+        }
+        rec.t = t;
+        rec.p = p;
+        (rec.u, rec.v) = (u, v);
+        rec.dpdu = self.point(self.a1 - self.a0, 0.0, 0.0);
+        rec.dpdv = self.point(0.0, self.b1 - self.b0, 0.0);
+        rec.set_face_normal(r, self.point(0.0, 0.0, 1.0));
+        rec.material = &self.material;
+
+        true
+    }
+
+    fn bounding_box(&self, _time_frame: (f64, f64)) -> Option<Aabb> {
+        let corners = Aabb::new(
+            self.point(self.a0, self.b0, self.k),
+            self.point(self.a1, self.b1, self.k),
+        );
+        Some(corners.with_volume())
+    }
+}
+
+impl Light for AaRect {
+    fn pdf_value(&self, r: &Ray) -> f64 {
+        planar_pdf_value(self, self.area(), r)
+    }
+
+    fn random(&self, origin: Point3, _time: f64, rng: &mut dyn RngCore) -> Vec3 {
+        let random_point = self.point(
+            rng.gen_range(self.a0..self.a1),
+            rng.gen_range(self.b0..self.b1),
+            self.k,
+        );
+        random_point - origin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AaRect;
+    use crate::{
+        raytracer::{hittable::material::Material, HitRecord, Hittable, Light, Ray},
+        vec3::{Point3, Vec3},
+    };
+
+    #[test]
+    fn hit() {
+        let rect = AaRect::xz(-1., 1., -1., 1., 2., Material::None);
+        let mut rec = HitRecord::new();
+
+        let ray = Ray::new(Point3::zeros(), Vec3::up(), 0.0);
+        assert!(rect.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!(rec.t, 2.0);
+        assert_eq!(rec.normal, -Vec3::up());
+        assert!(!rec.front_face);
+
+        let ray = Ray::new(Point3::zeros(), Vec3::new(1., 1., 0.), 0.0);
+        assert!(!rect.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+    }
+
+    #[test]
+    fn pdf_value() {
+        // A 2x2 light seen straight on from a distance of 2
+        let rect = AaRect::xy(-1., 1., -1., 1., -2., Material::None);
+        let ray = Ray::new(Point3::zeros(), Vec3::new(0., 0., -1.), 0.0);
+        assert_eq!(rect.pdf_value(&ray), 1.0);
+
+        let ray = Ray::new(Point3::zeros(), Vec3::new(0., 0., 1.), 0.0);
+        assert_eq!(rect.pdf_value(&ray), 0.0);
+    }
+}
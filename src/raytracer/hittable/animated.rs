@@ -0,0 +1,429 @@
+use super::{aabb::Aabb, keyframes, HitRecord, Hittable, Interval};
+use crate::{
+    raytracer::Ray,
+    vec3::{Point3, Vec3},
+};
+use std::{f64::consts::PI, sync::Arc};
+
+// Largest rotation between two of the poses whose boxes bound a motion
+const BOUNDS_STEP_ANGLE: f64 = PI / 36.0;
+
+// Unit quaternion, for rotations
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Quaternion {
+    w: f64,
+    v: Vec3,
+}
+
+impl Quaternion {
+    const IDENTITY: Quaternion = Quaternion {
+        w: 1.0,
+        v: Vec3 {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        },
+    };
+
+    fn from_axis_angle(axis: Vec3, angle: f64) -> Self {
+        let half = 0.5 * angle;
+        Quaternion {
+            w: half.cos(),
+            v: half.sin() * axis.normalize(),
+        }
+    }
+    // Rotation by `self` after `q`
+    fn compose(self, q: Quaternion) -> Self {
+        Quaternion {
+            w: self.w * q.w - self.v.dot(q.v),
+            v: self.w * q.v + q.w * self.v + self.v.cross(q.v),
+        }
+    }
+    fn inverse(self) -> Self {
+        Quaternion {
+            w: self.w,
+            v: -self.v,
+        }
+    }
+    fn rotate(self, p: Vec3) -> Vec3 {
+        let t = 2.0 * self.v.cross(p);
+        p + self.w * t + self.v.cross(t)
+    }
+    fn dot(self, q: Quaternion) -> f64 {
+        self.w * q.w + self.v.dot(q.v)
+    }
+    // Angle of the rotation taking `self` to `q`
+    fn angle_to(self, q: Quaternion) -> f64 {
+        2.0 * self.dot(q).abs().min(1.0).acos()
+    }
+    // Rotation at `s` of the way from `self` to `q` along the shortest arc,
+    // turning at a constant rate
+    fn slerp(self, q: Quaternion, s: f64) -> Self {
+        let q = if self.dot(q) < 0.0 {
+            Quaternion { w: -q.w, v: -q.v }
+        } else {
+            q
+        };
+        let cos_theta = self.dot(q).min(1.0);
+        let (a, b) = if cos_theta > 0.9995 {
+            // Nearly the same rotation, where linear interpolation is exact
+            // enough and the sine below vanishes
+            (1.0 - s, s)
+        } else {
+            let theta = cos_theta.acos();
+            (
+                ((1.0 - s) * theta).sin() / theta.sin(),
+                (s * theta).sin() / theta.sin(),
+            )
+        };
+        let w = a * self.w + b * q.w;
+        let v = a * self.v + b * q.v;
+        let norm = (w * w + v.length_squared()).sqrt();
+        Quaternion {
+            w: w / norm,
+            v: v / norm,
+        }
+    }
+}
+
+// Scaling along the axes, then a rotation around the origin, then a
+// translation. Kept apart rather than as a matrix so that keyframes
+// interpolate each of them, rotations turning instead of shearing.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform {
+    scale: Vec3,
+    rotation: Quaternion,
+    translation: Vec3,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Transform {
+            scale: Vec3::ones(),
+            rotation: Quaternion::IDENTITY,
+            translation: Vec3::zeros(),
+        }
+    }
+    pub fn translation(offset: Vec3) -> Self {
+        Self::identity().then_translate(offset)
+    }
+    // Counter-clockwise around `axis`, in degrees
+    pub fn rotation(axis: Vec3, degrees: f64) -> Self {
+        Self::identity().then_rotate(axis, degrees)
+    }
+    pub fn scaling(factors: Vec3) -> Self {
+        assert!(
+            factors.x != 0.0 && factors.y != 0.0 && factors.z != 0.0,
+            "A scaling needs non-zero factors"
+        );
+        Transform {
+            scale: factors,
+            ..Self::identity()
+        }
+    }
+    // Transforms applied after `self`. Only scalings may be non-uniform,
+    // which is what `scaling` is for, coming first.
+    pub fn then_translate(self, offset: Vec3) -> Self {
+        Transform {
+            translation: self.translation + offset,
+            ..self
+        }
+    }
+    pub fn then_rotate(self, axis: Vec3, degrees: f64) -> Self {
+        let rotation = Quaternion::from_axis_angle(axis, degrees.to_radians());
+        Transform {
+            rotation: rotation.compose(self.rotation),
+            translation: rotation.rotate(self.translation),
+            ..self
+        }
+    }
+    pub fn then_scale(self, factor: f64) -> Self {
+        assert!(factor != 0.0, "A scaling needs a non-zero factor");
+        Transform {
+            scale: factor * self.scale,
+            translation: factor * self.translation,
+            ..self
+        }
+    }
+
+    pub fn point(&self, p: Point3) -> Point3 {
+        self.vector(p) + self.translation
+    }
+    pub fn vector(&self, v: Vec3) -> Vec3 {
+        self.rotation.rotate(self.scale * v)
+    }
+    // Normals transform with the inverse transpose, keeping them
+    // perpendicular to the surface, and are left unnormalized
+    pub fn normal(&self, n: Vec3) -> Vec3 {
+        self.rotation.rotate(Vec3::new(
+            n.x / self.scale.x,
+            n.y / self.scale.y,
+            n.z / self.scale.z,
+        ))
+    }
+    pub fn inverse_point(&self, p: Point3) -> Point3 {
+        self.inverse_vector(p - self.translation)
+    }
+    pub fn inverse_vector(&self, v: Vec3) -> Vec3 {
+        let v = self.rotation.inverse().rotate(v);
+        Vec3::new(v.x / self.scale.x, v.y / self.scale.y, v.z / self.scale.z)
+    }
+    // Transform at `s` of the way from `self` to `other`
+    fn interpolate(&self, other: &Transform, s: f64) -> Self {
+        Transform {
+            scale: (1.0 - s) * self.scale + s * other.scale,
+            rotation: self.rotation.slerp(other.rotation, s),
+            translation: (1.0 - s) * self.translation + s * other.translation,
+        }
+    }
+    // Box around the image of `bbox`, i.e. around its transformed corners
+    fn bounding_box(&self, bbox: &Aabb) -> Aabb {
+        let (min, max) = (bbox.minimum, bbox.maximum);
+        (0..8)
+            .map(|i| {
+                let corner = Point3::new(
+                    if i & 1 == 0 { min.x } else { max.x },
+                    if i & 2 == 0 { min.y } else { max.y },
+                    if i & 4 == 0 { min.z } else { max.z },
+                );
+                let p = self.point(corner);
+                Aabb::new(p, p)
+            })
+            .reduce(|a, b| Aabb::surronding_box(&a, &b))
+            .unwrap()
+    }
+}
+
+// Object moved by transforms given at keyframe times and interpolated at the
+// time of each ray, for motion blur. Before the first keyframe and after the
+// last, the object stays in place. Rotations take the shortest way between
+// keyframes, so turns of half a revolution or more need keyframes in
+// between.
+pub struct Animated {
+    object: Arc<dyn Hittable + Send + Sync>,
+    keyframes: Vec<(f64, Transform)>,
+}
+
+impl Animated {
+    pub fn new(object: Arc<dyn Hittable + Send + Sync>, keyframes: Vec<(f64, Transform)>) -> Self {
+        Animated {
+            object,
+            keyframes: keyframes::sorted(keyframes),
+        }
+    }
+    // Object transformed the same way all the time
+    pub fn fixed(object: Arc<dyn Hittable + Send + Sync>, transform: Transform) -> Self {
+        Self::new(object, vec![(0.0, transform)])
+    }
+    pub fn transform(&self, time: f64) -> Transform {
+        keyframes::interpolate_with(&self.keyframes, time, |a, b, s| a.interpolate(&b, s))
+    }
+    fn local_ray(transform: &Transform, r: &Ray) -> Ray {
+        Ray {
+            wavelength: r.wavelength,
+            ..Ray::new(
+                transform.inverse_point(r.origin),
+                transform.inverse_vector(r.direction),
+                r.time,
+            )
+        }
+    }
+    // Distances along the ray are the same in both spaces, the transform
+    // being affine
+    fn to_world(transform: &Transform, rec: &mut HitRecord) {
+        rec.p = transform.point(rec.p);
+        rec.normal = transform.normal(rec.normal).normalize();
+        rec.dpdu = transform.vector(rec.dpdu);
+        rec.dpdv = transform.vector(rec.dpdv);
+    }
+}
+
+impl Hittable for Animated {
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        let transform = self.transform(r.time);
+        if !self
+            .object
+            .hit(&Self::local_ray(&transform, r), t_min, t_max, rec)
+        {
+            return false;
+        }
+        // Which side the normal faces the ray is kept by the transform
+        Self::to_world(&transform, rec);
+        true
+    }
+
+    fn bounding_box(&self, time_frame: (f64, f64)) -> Option<Aabb> {
+        let local = self.object.bounding_box(time_frame)?;
+        // Farthest a point of the box can be from the origin along each axis
+        let extent = Vec3::new(
+            local.minimum.x.abs().max(local.maximum.x.abs()),
+            local.minimum.y.abs().max(local.maximum.y.abs()),
+            local.minimum.z.abs().max(local.maximum.z.abs()),
+        );
+
+        // Between keyframes each part of the transform changes steadily
+        let (start, end) = time_frame;
+        let mut times = vec![start];
+        times.extend(
+            self.keyframes
+                .iter()
+                .map(|&(t, _)| t)
+                .filter(|&t| start < t && t < end),
+        );
+        times.push(end);
+
+        let mut bbox = self.transform(start).bounding_box(&local);
+        for window in times.windows(2) {
+            let (a, b) = (self.transform(window[0]), self.transform(window[1]));
+            let angle = a.rotation.angle_to(b.rotation);
+            let steps = (angle / BOUNDS_STEP_ANGLE).ceil().max(1.0) as usize;
+            let mut from = a;
+            for step in 1..=steps {
+                let to = a.interpolate(&b, step as f64 / steps as f64);
+                // Scaling and translating alone move points along straight
+                // lines, covered by the boxes at both ends. Turning by θ
+                // strays at most θ/4 of their distance to the origin away.
+                let reach = (from.scale * extent)
+                    .length()
+                    .max((to.scale * extent).length());
+                let stray = 0.25 * from.rotation.angle_to(to.rotation) * reach * Vec3::ones();
+                let moved =
+                    Aabb::surronding_box(&from.bounding_box(&local), &to.bounding_box(&local));
+                bbox = Aabb::surronding_box(
+                    &bbox,
+                    &Aabb::new(moved.minimum - stray, moved.maximum + stray),
+                );
+                from = to;
+            }
+        }
+        Some(bbox)
+    }
+
+    fn hit_intervals<'a>(&'a self, r: &Ray) -> Vec<Interval<'a>> {
+        let transform = self.transform(r.time);
+        let mut intervals = self.object.hit_intervals(&Self::local_ray(&transform, r));
+        for interval in &mut intervals {
+            Self::to_world(&transform, &mut interval.enter);
+            Self::to_world(&transform, &mut interval.exit);
+        }
+        intervals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Animated, Transform};
+    use crate::{
+        raytracer::{hittable::material::Material, HitRecord, Hittable, Quad, Ray, Sphere},
+        vec3::{Point3, Vec3},
+    };
+    use rand::{thread_rng, Rng};
+    use std::sync::Arc;
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn transforms() {
+        let transform = Transform::scaling(Vec3::new(2., 1., 1.))
+            .then_rotate(Vec3::up(), 90.)
+            .then_translate(Vec3::new(0., 1., 0.));
+        let p = Point3::new(1., 0., 0.);
+        assert_near(transform.point(p), Point3::new(0., 1., -2.));
+        assert_near(transform.inverse_point(transform.point(p)), p);
+        // Normals stay perpendicular to transformed tangents
+        let (tangent, normal) = (Vec3::new(1., -1., 0.), Vec3::new(1., 1., 0.));
+        assert!(
+            transform
+                .vector(tangent)
+                .dot(transform.normal(normal))
+                .abs()
+                < 1e-12
+        );
+        // Moving after rotating moves the translation along
+        let turned = Transform::translation(Vec3::new(1., 0., 0.)).then_rotate(Vec3::up(), 180.);
+        assert_near(turned.point(Point3::zeros()), Point3::new(-1., 0., 0.));
+    }
+
+    #[test]
+    fn keyframes() {
+        let sphere = Arc::new(Sphere::new(Point3::zeros(), 1., Material::None));
+        let animated = Animated::new(
+            sphere,
+            vec![
+                (
+                    1.0,
+                    Transform::rotation(Vec3::up(), 90.).then_translate(Vec3::new(4., 0., 0.)),
+                ),
+                (0.0, Transform::identity()),
+            ],
+        );
+        let p = Point3::new(1., 0., 0.);
+        assert_near(animated.transform(-1.0).point(p), p);
+        assert_near(animated.transform(2.0).point(p), Point3::new(4., 0., -1.));
+        let half = animated.transform(0.5).point(p);
+        let turn = std::f64::consts::FRAC_PI_4;
+        assert_near(half, Point3::new(2. + turn.cos(), 0., -turn.sin()));
+
+        // Rays meet the sphere where it is at their time
+        let mut rec = HitRecord::new();
+        let ray = |time| Ray::new(Point3::new(4., 5., 0.), -Vec3::up(), time);
+        assert!(!animated.hit(&ray(0.0), 0.001, f64::INFINITY, &mut rec));
+        assert!(animated.hit(&ray(1.0), 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 4.0).abs() < 1e-9);
+        assert_near(rec.p, Point3::new(4., 1., 0.));
+        assert_near(rec.normal, Vec3::up());
+        assert!(rec.front_face);
+    }
+
+    #[test]
+    fn bounding_box() {
+        // A long board turning a quarter turn while moving up
+        let board = Arc::new(Quad::cuboid(
+            Point3::new(1., 0., 0.),
+            Vec3::new(2., 0., 0.),
+            Vec3::new(0., 0.2, 0.),
+            Vec3::new(0., 0., 0.2),
+            Material::None,
+        ));
+        let animated = Animated::new(
+            board.clone(),
+            vec![
+                (0.0, Transform::identity()),
+                (
+                    1.0,
+                    Transform::rotation(Vec3::up(), 90.).then_translate(Vec3::up()),
+                ),
+            ],
+        );
+        let bbox = animated.bounding_box((0.0, 1.0)).unwrap();
+        let local = board.bounding_box((0.0, 1.0)).unwrap();
+        let mut rng = thread_rng();
+        for _ in 0..10_000 {
+            let transform = animated.transform(rng.gen_range(0.0..1.0));
+            let p = transform.point(Point3::new(
+                rng.gen_range(local.minimum.x..local.maximum.x),
+                rng.gen_range(local.minimum.y..local.maximum.y),
+                rng.gen_range(local.minimum.z..local.maximum.z),
+            ));
+            for a in 0..3 {
+                assert!(bbox.minimum[a] <= p[a] && p[a] <= bbox.maximum[a]);
+            }
+        }
+        // Swept by the far end of the board, not much bigger
+        assert!(bbox.maximum.x < 3.1 && bbox.minimum.z > -3.1);
+        assert!(bbox.maximum.y < 1.3);
+
+        // Only the part of the motion within the time frame counts
+        let start = animated.bounding_box((0.0, 0.0)).unwrap();
+        assert!((start.maximum.x - local.maximum.x).abs() < 1e-9);
+        assert!((start.minimum.z - local.minimum.z).abs() < 1e-9);
+    }
+}
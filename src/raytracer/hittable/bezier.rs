@@ -0,0 +1,56 @@
+use crate::vec3::{Point3, Vec3};
+
+// Point of the curve with control points `p` at `u`
+pub(super) fn bezier(p: &[Point3; 4], u: f64) -> Point3 {
+    blossom(p, u, u, u)
+}
+
+pub(super) fn bezier_derivative(p: &[Point3; 4], u: f64) -> Vec3 {
+    let v = 1.0 - u;
+    3.0 * (v * v * (p[1] - p[0]) + 2.0 * u * v * (p[2] - p[1]) + u * u * (p[3] - p[2]))
+}
+
+// De Casteljau's steps with a different parameter at each level. The control
+// points of the part of the curve over [a, b] are the blossoms at
+// (a, a, a), (a, a, b), (a, b, b) and (b, b, b).
+fn blossom(p: &[Point3; 4], a: f64, b: f64, c: f64) -> Point3 {
+    let lerp = |t: f64, x: Point3, y: Point3| (1.0 - t) * x + t * y;
+    let q = [
+        lerp(a, p[0], p[1]),
+        lerp(a, p[1], p[2]),
+        lerp(a, p[2], p[3]),
+    ];
+    let r = [lerp(b, q[0], q[1]), lerp(b, q[1], q[2])];
+    lerp(c, r[0], r[1])
+}
+
+// Control points of the part of the curve over [a, b]
+pub(super) fn sub_curve(p: &[Point3; 4], a: f64, b: f64) -> [Point3; 4] {
+    [
+        blossom(p, a, a, a),
+        blossom(p, a, a, b),
+        blossom(p, a, b, b),
+        blossom(p, b, b, b),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bezier, sub_curve};
+    use crate::vec3::Point3;
+
+    #[test]
+    fn splitting() {
+        let p = [
+            Point3::new(0., 0., 0.),
+            Point3::new(1., 2., 0.),
+            Point3::new(2., -1., 1.),
+            Point3::new(3., 0., 0.),
+        ];
+        let part = sub_curve(&p, 0.25, 0.75);
+        for s in [0.0, 0.3, 1.0] {
+            let expected = bezier(&p, 0.25 + 0.5 * s);
+            assert!((bezier(&part, s) - expected).length() < 1e-12);
+        }
+    }
+}
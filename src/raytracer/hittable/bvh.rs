@@ -1,50 +1,77 @@
-use super::{aabb::Aabb, HitRecord, Hittable};
+use super::{aabb::Aabb, HitRecord, Hittable, HittableList};
 use crate::raytracer::Ray;
-use rand::{rngs::ThreadRng, Rng};
+use rand::{Rng, RngCore};
 use std::{cmp::Ordering, sync::Arc};
 
 pub struct BvhNode {
     left: Arc<dyn Hittable + Sync + Send>,
     right: Arc<dyn Hittable + Sync + Send>,
-    bounding_box: Aabb,
+    // None for the node holding unbounded objects, which every ray is tested against
+    bounding_box: Option<Aabb>,
 }
 
 impl Hittable for BvhNode {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
-        if !self.bounding_box.hit(r, t_min, t_max, rec) {
-            return false;
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        if let Some(bounding_box) = &self.bounding_box {
+            if !bounding_box.hit(r, t_min, t_max, rec) {
+                return false;
+            }
         }
         let hit_left = self.left.hit(r, t_min, t_max, rec);
-        let hit_right = self.right.hit(r, t_min, t_max, rec);
+        // Leaves hold their object on both sides
+        if Arc::ptr_eq(&self.left, &self.right) {
+            return hit_left;
+        }
+        let hit_right = self
+            .right
+            .hit(r, t_min, if hit_left { rec.t } else { t_max }, rec);
 
         hit_left || hit_right
     }
 
     fn bounding_box(&self, _time_frame: (f64, f64)) -> Option<Aabb> {
-        Some(self.bounding_box)
+        self.bounding_box
     }
 }
 impl BvhNode {
     pub fn new(
-        list: &Vec<Arc<dyn Hittable + Send + Sync>>,
-        rng: &mut ThreadRng,
+        list: &[Arc<dyn Hittable + Send + Sync>],
+        rng: &mut dyn RngCore,
         start: usize,
         end: usize,
         time_frame: &(f64, f64),
     ) -> BvhNode {
-        let mut list = list.clone();
+        // Unbounded objects, like planes, are kept out of the hierarchy and
+        // sit next to it under a root without a box
+        let (bounded, unbounded): (Vec<_>, Vec<_>) = list[start..end]
+            .iter()
+            .cloned()
+            .partition(|object| object.bounding_box(*time_frame).is_some());
+        if !unbounded.is_empty() {
+            let left: Arc<dyn Hittable + Sync + Send> = if bounded.is_empty() {
+                Arc::new(HittableList::new())
+            } else {
+                Arc::new(BvhNode::new(&bounded, rng, 0, bounded.len(), time_frame))
+            };
+            return BvhNode {
+                left,
+                right: Arc::new(HittableList { objects: unbounded }),
+                bounding_box: None,
+            };
+        }
+
+        // Only the objects under this node are copied, or building would be
+        // quadratic in their number
+        let mut list = list[start..end].to_vec();
         let axis = rng.gen_range(0..3);
-        let object_span = end - start;
+        let object_span = list.len();
 
         // let (left, right) =
         match object_span {
             1 => BvhNode {
                 right: list[0].clone(),
                 left: list[0].clone(),
-                bounding_box: Aabb::surronding_box(
-                    &list[0].bounding_box(*time_frame).unwrap(),
-                    &list[0].bounding_box(*time_frame).unwrap(),
-                ),
+                bounding_box: list[0].bounding_box(*time_frame),
             },
             // 2 => match Aabb::axis_compare(&list[start], &list[start + 1], axis) {
             //     Ok(true) => (&list[start], &list[start + 1]),
@@ -52,9 +79,9 @@ impl BvhNode {
             //     _ => panic!(),
             // },
             _ => {
-                list[start..end].sort_by(|a, b| -> Ordering {
+                list.sort_by(|a, b| -> Ordering {
                     if let (Some(box_a), Some(box_b)) =
-                        (a.bounding_box((0.0, 0.0)), b.bounding_box((0.0, 0.0)))
+                        (a.bounding_box(*time_frame), b.bounding_box(*time_frame))
                     {
                         return box_a.minimum[axis]
                             .partial_cmp(&box_b.minimum[axis])
@@ -62,15 +89,15 @@ impl BvhNode {
                     }
                     panic!();
                 });
-                let mid = start + object_span / 2;
+                let mid = object_span / 2;
 
-                let left = Arc::new(BvhNode::new(&list, rng, start, mid, time_frame));
-                let right = Arc::new(BvhNode::new(&list, rng, mid, end, time_frame));
+                let left = Arc::new(BvhNode::new(&list, rng, 0, mid, time_frame));
+                let right = Arc::new(BvhNode::new(&list, rng, mid, object_span, time_frame));
                 BvhNode {
-                    bounding_box: Aabb::surronding_box(
+                    bounding_box: Some(Aabb::surronding_box(
                         &left.bounding_box(*time_frame).unwrap(),
                         &right.bounding_box(*time_frame).unwrap(),
-                    ),
+                    )),
                     left,
                     right,
                 }
@@ -78,3 +105,76 @@ impl BvhNode {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::BvhNode;
+    use crate::{
+        raytracer::{hittable::material::Material, HitRecord, Hittable, HittableList, Ray, Sphere},
+        vec3::{Point3, Vec3},
+    };
+    use rand::{thread_rng, Rng};
+    use std::sync::Arc;
+
+    #[test]
+    fn closest_hits() {
+        // Spheres in a row, enough for leaves deep in the hierarchy
+        let spheres: Vec<Arc<dyn Hittable + Send + Sync>> = (0..8)
+            .map(|i| {
+                Arc::new(Sphere::new(
+                    Point3::new(3. * i as f64, 0., 0.),
+                    1.,
+                    Material::None,
+                )) as Arc<dyn Hittable + Send + Sync>
+            })
+            .collect();
+        let bvh = BvhNode::new(&spheres, &mut thread_rng(), 0, spheres.len(), &(0.0, 0.0));
+        let mut rec = HitRecord::new();
+
+        // Each leaf holds its own sphere
+        for i in 0..8 {
+            let x = 3. * i as f64;
+            let ray = Ray::new(Point3::new(x, 5., 0.), -Vec3::up(), 0.0);
+            assert!(bvh.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+            assert_eq!(rec.p, Point3::new(x, 1., 0.));
+        }
+        // Along the row, the nearest sphere hides the others from either end
+        let ray = Ray::new(Point3::new(-5., 0., 0.), Vec3::new(1., 0., 0.), 0.0);
+        assert!(bvh.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!(rec.t, 4.0);
+        let ray = Ray::new(Point3::new(26., 0., 0.), Vec3::new(-1., 0., 0.), 0.0);
+        assert!(bvh.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!(rec.t, 4.0);
+    }
+
+    #[test]
+    fn many_objects() {
+        // Closest hits match those of testing every object in turn
+        let mut rng = thread_rng();
+        let mut list = HittableList::new();
+        for _ in 0..5000 {
+            list.add(Arc::new(Sphere::new(
+                Vec3::rand(&mut rng, -10.0, 10.0),
+                rng.gen_range(0.05..0.3),
+                Material::None,
+            )));
+        }
+        let bvh = BvhNode::new(&list.objects, &mut rng, 0, list.objects.len(), &(0.0, 0.0));
+        let mut hits = 0;
+        for _ in 0..1000 {
+            let ray = Ray::new(
+                Vec3::rand(&mut rng, -12.0, 12.0),
+                Vec3::rand_unit(&mut rng),
+                0.0,
+            );
+            let (mut expected, mut found) = (HitRecord::new(), HitRecord::new());
+            let hit = list.hit(&ray, 0.001, f64::INFINITY, &mut expected);
+            assert_eq!(bvh.hit(&ray, 0.001, f64::INFINITY, &mut found), hit);
+            if hit {
+                hits += 1;
+                assert_eq!((found.t, found.p), (expected.t, expected.p));
+            }
+        }
+        assert!(hits > 100);
+    }
+}
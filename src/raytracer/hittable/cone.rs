@@ -0,0 +1,168 @@
+use super::{
+    aabb::Aabb,
+    disk::{circle_extent, Disk},
+    material::Material,
+    HitRecord, Hittable,
+};
+use crate::{
+    raytracer::Ray,
+    vec3::{Onb, Point3, Vec3},
+};
+use std::f64::consts::PI;
+
+// Cone with a base of `radius` around `base` and its apex at `base + axis`,
+// closed by a disk. On its side u goes around the axis and v up to the apex.
+pub struct Cone {
+    base: Point3,
+    frame: Onb,
+    height: f64,
+    radius: f64,
+    material: Material,
+    cap: Disk,
+}
+
+impl Cone {
+    pub fn new(base: Point3, axis: Vec3, radius: f64, material: Material) -> Self {
+        Cone {
+            base,
+            frame: Onb::from_w(axis),
+            height: axis.length(),
+            radius,
+            cap: Disk::new(base, -axis, radius, material.clone()),
+            material,
+        }
+    }
+    fn hit_side<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        let origin = self.frame.to_local(r.origin - self.base);
+        let direction = self.frame.to_local(r.direction);
+
+        // x² + y² = k² (h - z)², the radius shrinking by k per unit of height
+        let k2 = (self.radius / self.height).powi(2);
+        let below_apex = self.height - origin.z;
+        let a =
+            direction.x * direction.x + direction.y * direction.y - k2 * direction.z * direction.z;
+        let half_b =
+            origin.x * direction.x + origin.y * direction.y + k2 * below_apex * direction.z;
+        let c = origin.x * origin.x + origin.y * origin.y - k2 * below_apex * below_apex;
+        let roots = if a.abs() < 1e-12 {
+            // Ray parallel to the side, crossing the double cone once
+            if half_b == 0.0 {
+                return false;
+            }
+            [-c / (2.0 * half_b); 2]
+        } else {
+            let discriminant = half_b * half_b - a * c;
+            if discriminant < 0.0 {
+                return false;
+            }
+            let sqrtd = discriminant.sqrt();
+            let (t0, t1) = ((-half_b - sqrtd) / a, (-half_b + sqrtd) / a);
+            [t0.min(t1), t0.max(t1)]
+        };
+
+        // Nearest root first, the far one showing through cutout holes
+        for root in roots {
+            if root < t_min || t_max < root {
+                continue;
+            }
+            let local = origin + root * direction;
+            // Skip the mirrored cone above the apex
+            if local.z < 0.0 || local.z > self.height {
+                continue;
+            }
+            let phi = local.y.atan2(local.x).rem_euclid(2.0 * PI);
+            let (u, v) = (phi / (2.0 * PI), local.z / self.height);
+            let p = r.at(root);
+            if !self.material.is_opaque_at(r, root, u, v, p) {
+                continue;
+            }
+            let radial = Vec3::new(phi.cos(), phi.sin(), 0.0);
+            rec.t = root;
+            rec.p = p;
+            (rec.u, rec.v) = (u, v);
+            rec.dpdu = self
+                .frame
+                .local(2.0 * PI * Vec3::new(-local.y, local.x, 0.0));
+            rec.dpdv = self
+                .frame
+                .local(-self.radius * radial + Vec3::new(0.0, 0.0, self.height));
+            let out_normal = self
+                .frame
+                .local(self.height * radial + Vec3::new(0.0, 0.0, self.radius))
+                .normalize();
+            rec.set_face_normal(r, out_normal);
+            rec.material = &self.material;
+            return true;
+        }
+        false
+    }
+}
+
+impl Hittable for Cone {
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        let hit_side = self.hit_side(r, t_min, t_max, rec);
+        let hit_cap = self
+            .cap
+            .hit(r, t_min, if hit_side { rec.t } else { t_max }, rec);
+        hit_side || hit_cap
+    }
+
+    fn bounding_box(&self, _time_frame: (f64, f64)) -> Option<Aabb> {
+        let extent = circle_extent(self.frame.w, self.radius);
+        let apex = self.base + self.height * self.frame.w;
+        Some(Aabb::surronding_box(
+            &Aabb::new(self.base - extent, self.base + extent),
+            &Aabb::new(apex, apex),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cone;
+    use crate::{
+        raytracer::{hittable::material::Material, HitRecord, Hittable, Ray},
+        vec3::{Point3, Vec3},
+    };
+
+    #[test]
+    fn hit() {
+        let cone = Cone::new(Point3::zeros(), 2.0 * Vec3::up(), 1., Material::None);
+        let mut rec = HitRecord::new();
+
+        // Halfway up, the side is at half the radius and leans by 1 in 2
+        let ray = Ray::new(Point3::new(-3., 1., 0.), Vec3::new(1., 0., 0.), 0.0);
+        assert!(cone.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 2.5).abs() < 1e-12);
+        assert!((rec.normal - Vec3::new(-2., 1., 0.).normalize()).length() < 1e-12);
+        assert!((rec.v - 0.5).abs() < 1e-12);
+        assert!(rec.front_face);
+
+        // Straight down onto the apex then through the base
+        let ray = Ray::new(Point3::new(0., 5., 0.), -Vec3::up(), 0.0);
+        assert!(cone.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 3.0).abs() < 1e-9);
+        let ray = Ray::new(Point3::new(0.5, -1., 0.), Vec3::up(), 0.0);
+        assert!(cone.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!((rec.t, rec.normal), (1.0, -Vec3::up()));
+
+        // Above the apex, where the mirrored cone would be
+        let ray = Ray::new(Point3::new(-3., 3., 0.), Vec3::new(1., 0., 0.), 0.0);
+        assert!(!cone.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+    }
+
+    #[test]
+    fn dpduv_are_tangent() {
+        let cone = Cone::new(
+            Point3::new(1., 2., 3.),
+            Vec3::new(0.3, 1., -0.5),
+            0.7,
+            Material::None,
+        );
+        let mut rec = HitRecord::new();
+        let ray = Ray::new(Point3::new(5., 2.3, 3.), Vec3::new(-1., 0., 0.1), 0.0);
+        assert!(cone.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert!(rec.dpdu.dot(rec.normal).abs() < 1e-9);
+        assert!(rec.dpdv.dot(rec.normal).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,249 @@
+use super::{aabb::Aabb, HitRecord, Hittable, Interval};
+use crate::{raytracer::Ray, vec3::Point3};
+use std::sync::Arc;
+
+// Way the insides of both operands combine
+#[derive(Debug, Copy, Clone)]
+enum Operation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl Operation {
+    fn inside(self, in_a: bool, in_b: bool) -> bool {
+        match self {
+            Operation::Union => in_a || in_b,
+            Operation::Intersection => in_a && in_b,
+            Operation::Difference => in_a && !in_b,
+        }
+    }
+}
+
+// Constructive solid geometry: the solid made of the points inside either,
+// both, or only the first of two closed objects, whose surfaces keep their
+// own materials
+pub struct Csg {
+    a: Arc<dyn Hittable + Send + Sync>,
+    b: Arc<dyn Hittable + Send + Sync>,
+    operation: Operation,
+}
+
+impl Csg {
+    pub fn union(a: Arc<dyn Hittable + Send + Sync>, b: Arc<dyn Hittable + Send + Sync>) -> Self {
+        Self::new(a, b, Operation::Union)
+    }
+    pub fn intersection(
+        a: Arc<dyn Hittable + Send + Sync>,
+        b: Arc<dyn Hittable + Send + Sync>,
+    ) -> Self {
+        Self::new(a, b, Operation::Intersection)
+    }
+    // `a` carved by `b`
+    pub fn difference(
+        a: Arc<dyn Hittable + Send + Sync>,
+        b: Arc<dyn Hittable + Send + Sync>,
+    ) -> Self {
+        Self::new(a, b, Operation::Difference)
+    }
+    fn new(
+        a: Arc<dyn Hittable + Send + Sync>,
+        b: Arc<dyn Hittable + Send + Sync>,
+        operation: Operation,
+    ) -> Self {
+        Csg { a, b, operation }
+    }
+}
+
+impl Hittable for Csg {
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        for interval in self.hit_intervals(r) {
+            for boundary in [interval.enter, interval.exit] {
+                if boundary.t >= t_max {
+                    return false;
+                }
+                if boundary.t >= t_min {
+                    *rec = boundary;
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    fn bounding_box(&self, time_frame: (f64, f64)) -> Option<Aabb> {
+        let box_a = self.a.bounding_box(time_frame);
+        let box_b = self.b.bounding_box(time_frame);
+        match self.operation {
+            Operation::Union => Some(Aabb::surronding_box(&box_a?, &box_b?)),
+            Operation::Intersection => match (box_a, box_b) {
+                (Some(box_a), Some(box_b)) => {
+                    let minimum = Point3::new(
+                        box_a.minimum.x.max(box_b.minimum.x),
+                        box_a.minimum.y.max(box_b.minimum.y),
+                        box_a.minimum.z.max(box_b.minimum.z),
+                    );
+                    let maximum = Point3::new(
+                        box_a.maximum.x.min(box_b.maximum.x),
+                        box_a.maximum.y.min(box_b.maximum.y),
+                        box_a.maximum.z.min(box_b.maximum.z),
+                    );
+                    // Disjoint boxes leave an empty box, which no ray hits
+                    Some(Aabb::new(minimum, maximum))
+                }
+                (box_a, box_b) => box_a.or(box_b),
+            },
+            Operation::Difference => box_a,
+        }
+    }
+
+    fn hit_intervals<'a>(&'a self, r: &Ray) -> Vec<Interval<'a>> {
+        // Walk through the boundaries of both operands in order, keeping
+        // track of which ones the line is inside of
+        let mut boundaries: Vec<(HitRecord<'a>, bool)> = Vec::new();
+        for (intervals, is_a) in [
+            (self.a.hit_intervals(r), true),
+            (self.b.hit_intervals(r), false),
+        ] {
+            for interval in intervals {
+                boundaries.push((interval.enter, is_a));
+                boundaries.push((interval.exit, is_a));
+            }
+        }
+        boundaries.sort_by(|x, y| x.0.t.partial_cmp(&y.0.t).unwrap());
+
+        let mut intervals = Vec::new();
+        let (mut in_a, mut in_b) = (false, false);
+        let mut enter = HitRecord::new();
+        for (mut boundary, is_a) in boundaries {
+            let was_inside = self.operation.inside(in_a, in_b);
+            if is_a {
+                in_a = !in_a;
+            } else {
+                in_b = !in_b;
+            }
+            // Normals always face the ray, only which side of the result the
+            // boundary is changes, e.g. leaving a carved out part enters the
+            // difference
+            match (was_inside, self.operation.inside(in_a, in_b)) {
+                (false, true) => {
+                    boundary.front_face = true;
+                    enter = boundary;
+                }
+                (true, false) => {
+                    boundary.front_face = false;
+                    intervals.push(Interval {
+                        enter,
+                        exit: boundary,
+                    });
+                }
+                _ => {}
+            }
+        }
+        intervals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Csg;
+    use crate::{
+        raytracer::{hittable::material::Material, HitRecord, Hittable, Plane, Quad, Ray, Sphere},
+        vec3::{Point3, Vec3},
+    };
+    use std::sync::Arc;
+
+    // Unit spheres centered at x = 0 and x = 1
+    fn spheres() -> (Arc<Sphere>, Arc<Sphere>) {
+        (
+            Arc::new(Sphere::new(Point3::zeros(), 1., Material::None)),
+            Arc::new(Sphere::new(Point3::new(1., 0., 0.), 1., Material::None)),
+        )
+    }
+
+    fn boundaries(object: &dyn Hittable, r: &Ray) -> Vec<(f64, f64)> {
+        object
+            .hit_intervals(r)
+            .iter()
+            .map(|interval| (interval.enter.t, interval.exit.t))
+            .collect()
+    }
+
+    #[test]
+    fn intervals() {
+        let (a, _) = spheres();
+        let ray = Ray::new(Point3::new(-5., 0., 0.), Vec3::new(1., 0., 0.), 0.0);
+        assert_eq!(boundaries(a.as_ref(), &ray), vec![(4., 6.)]);
+
+        // A plane bounds the half-space behind it
+        let floor = Plane::new(Point3::zeros(), Vec3::up(), Material::None);
+        let down = Ray::new(Point3::new(0., 2., 0.), -Vec3::up(), 0.0);
+        assert_eq!(boundaries(&floor, &down), vec![(2., f64::INFINITY)]);
+        let up = Ray::new(Point3::new(0., 2., 0.), Vec3::up(), 0.0);
+        assert_eq!(boundaries(&floor, &up), vec![(f64::NEG_INFINITY, -2.)]);
+    }
+
+    #[test]
+    fn operations() {
+        let (a, b) = spheres();
+        let ray = Ray::new(Point3::new(-5., 0., 0.), Vec3::new(1., 0., 0.), 0.0);
+        let union = Csg::union(a.clone(), b.clone());
+        assert_eq!(boundaries(&union, &ray), vec![(4., 7.)]);
+        let intersection = Csg::intersection(a.clone(), b.clone());
+        assert_eq!(boundaries(&intersection, &ray), vec![(5., 6.)]);
+        let difference = Csg::difference(a, b);
+        assert_eq!(boundaries(&difference, &ray), vec![(4., 5.)]);
+
+        // The carved face is seen from the inside of the carving sphere, but
+        // from the outside of the difference
+        let mut rec = HitRecord::new();
+        let ray = Ray::new(Point3::new(5., 0., 0.), Vec3::new(-1., 0., 0.), 0.0);
+        assert!(difference.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!(rec.t, 5.0);
+        assert!(rec.front_face);
+        assert_eq!(rec.normal, Vec3::new(1., 0., 0.));
+
+        // Starting inside, the first hit leaves the solid
+        let ray = Ray::new(Point3::new(-0.5, 0., 0.), Vec3::new(1., 0., 0.), 0.0);
+        assert!(difference.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!(rec.t, 0.5);
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn carved_sphere() {
+        // Sphere minus the box over its upper octant, then cut by a plane
+        let sphere = Arc::new(Sphere::new(Point3::zeros(), 1., Material::None));
+        let corner = Arc::new(Quad::cuboid(
+            Point3::zeros(),
+            Vec3::new(2., 0., 0.),
+            Vec3::new(0., 2., 0.),
+            Vec3::new(0., 0., 2.),
+            Material::None,
+        ));
+        let carved = Arc::new(Csg::difference(sphere, corner));
+        let mut rec = HitRecord::new();
+
+        // Down through the missing octant onto the box's floor
+        let ray = Ray::new(Point3::new(0.5, 5., 0.5), -Vec3::up(), 0.0);
+        assert!(carved.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!(rec.t, 5.0);
+        assert_eq!(rec.normal, Vec3::up());
+        // Elsewhere the sphere is untouched
+        let ray = Ray::new(Point3::new(-0.5, 5., 0.5), -Vec3::up(), 0.0);
+        assert!(carved.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - (5.0 - 0.5f64.sqrt())).abs() < 1e-12);
+
+        let floor = Arc::new(Plane::new(
+            Point3::new(0., -0.5, 0.),
+            Vec3::up(),
+            Material::None,
+        ));
+        let cut = Csg::difference(carved.clone(), floor);
+        let ray = Ray::new(Point3::new(-0.5, -5., 0.5), Vec3::up(), 0.0);
+        assert!(cut.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!(rec.t, 4.5);
+        assert_eq!(rec.normal, -Vec3::up());
+        assert!(cut.bounding_box((0.0, 0.0)).is_some());
+    }
+}
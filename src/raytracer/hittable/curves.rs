@@ -0,0 +1,347 @@
+use super::{
+    aabb::Aabb,
+    bezier::{bezier, bezier_derivative, sub_curve},
+    bvh::BvhNode,
+    material::Material,
+    HitRecord, Hittable,
+};
+use crate::{
+    raytracer::Ray,
+    vec3::{Onb, Point3},
+};
+use rand::RngCore;
+use std::{f64::consts::SQRT_2, sync::Arc};
+
+// Pieces each curve is cut into, for tighter boxes in the hierarchy
+const SEGMENTS_PER_CURVE: usize = 4;
+
+// Cubic Bézier curve whose width goes linearly from `start_width` to
+// `end_width`
+#[derive(Debug, Copy, Clone)]
+pub struct Curve {
+    points: [Point3; 4],
+    widths: (f64, f64),
+}
+
+impl Curve {
+    pub fn new(points: [Point3; 4], start_width: f64, end_width: f64) -> Self {
+        Curve {
+            points,
+            widths: (start_width, end_width),
+        }
+    }
+}
+
+// Piece of a curve over [u.0, u.1], hit as a ribbon facing the ray
+struct Segment {
+    points: [Point3; 4],
+    u: (f64, f64),
+    // Of the whole curve
+    widths: (f64, f64),
+    material: Arc<Material>,
+}
+
+impl Segment {
+    fn width(&self, u: f64) -> f64 {
+        (1.0 - u) * self.widths.0 + u * self.widths.1
+    }
+    // Looks for the nearest hit between `z_min` and `z_max` with the ray
+    // along z through the origin, splitting the curve until its pieces can
+    // be taken for lines. Returns the distance and the parameter in the
+    // whole curve.
+    fn hit_local(
+        &self,
+        p: &[Point3; 4],
+        (u0, u1): (f64, f64),
+        depth: u32,
+        z_min: f64,
+        z_max: &mut f64,
+    ) -> Option<(f64, f64)> {
+        let half_width = 0.5 * self.width(u0).max(self.width(u1));
+        let lowest = |f: fn(&Point3) -> f64| p.iter().map(f).fold(f64::INFINITY, f64::min);
+        let highest = |f: fn(&Point3) -> f64| p.iter().map(f).fold(f64::NEG_INFINITY, f64::max);
+        if lowest(|p| p.x) - half_width > 0.0
+            || highest(|p| p.x) + half_width < 0.0
+            || lowest(|p| p.y) - half_width > 0.0
+            || highest(|p| p.y) + half_width < 0.0
+            || lowest(|p| p.z) - half_width > *z_max
+            || highest(|p| p.z) + half_width < z_min
+        {
+            return None;
+        }
+
+        if depth > 0 {
+            let middle = 0.5 * (u0 + u1);
+            let first = self.hit_local(
+                &sub_curve(p, 0.0, 0.5),
+                (u0, middle),
+                depth - 1,
+                z_min,
+                z_max,
+            );
+            let second = self.hit_local(
+                &sub_curve(p, 0.5, 1.0),
+                (middle, u1),
+                depth - 1,
+                z_min,
+                z_max,
+            );
+            return second.or(first);
+        }
+
+        // The origin must lie between the lines normal to the piece at both
+        // ends, so joints are not hit twice
+        if (p[1].y - p[0].y) * -p[0].y + p[0].x * (p[0].x - p[1].x) < 0.0
+            || (p[2].y - p[3].y) * -p[3].y + p[3].x * (p[3].x - p[2].x) < 0.0
+        {
+            return None;
+        }
+        // Closest point of the chord to the origin
+        let (dx, dy) = (p[3].x - p[0].x, p[3].y - p[0].y);
+        let length_squared = dx * dx + dy * dy;
+        if length_squared == 0.0 {
+            return None;
+        }
+        let w = (-(p[0].x * dx + p[0].y * dy) / length_squared).clamp(0.0, 1.0);
+        let u = (1.0 - w) * u0 + w * u1;
+        let closest = bezier(p, w);
+        let half_width = 0.5 * self.width(u);
+        if closest.x * closest.x + closest.y * closest.y > half_width * half_width
+            || closest.z < z_min
+            || closest.z > *z_max
+        {
+            return None;
+        }
+        *z_max = closest.z;
+        Some((closest.z, u))
+    }
+}
+
+impl Hittable for Segment {
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        // Work in a frame where the ray goes along z from the origin
+        let length = r.direction.length();
+        let frame = Onb::from_w(r.direction);
+        let local = self.points.map(|p| frame.to_local(p - r.origin));
+
+        // Split until pieces stray from their chords by a twentieth of the
+        // width, each split dividing the distance by 4
+        let mut deviation: f64 = 0.0;
+        for i in 0..2 {
+            let second = local[i] - 2.0 * local[i + 1] + local[i + 2];
+            deviation = deviation.max(second.x.abs()).max(second.y.abs());
+        }
+        let tolerance = 0.05 * self.width(self.u.0).max(self.width(self.u.1));
+        let depth = if deviation > 0.0 && tolerance > 0.0 {
+            ((SQRT_2 * 6.0 * deviation / (8.0 * tolerance)).log2() / 2.0).clamp(0.0, 10.0) as u32
+        } else {
+            0
+        };
+
+        let mut z_min = t_min * length;
+        loop {
+            let mut z_max = t_max * length;
+            let (z, u) = match self.hit_local(&local, self.u, depth, z_min, &mut z_max) {
+                Some(hit) => hit,
+                None => return false,
+            };
+            let t = z / length;
+            let p = r.at(t);
+            let width = self.width(u);
+
+            // Ribbons face the ray, their normals bent across them like those
+            // of a tube around the curve
+            let s = (u - self.u.0) / (self.u.1 - self.u.0);
+            let center = bezier(&self.points, s);
+            let dpdu = bezier_derivative(&self.points, s) / (self.u.1 - self.u.0);
+            let tangent = dpdu.normalize();
+            let towards_ray = -(r.direction - r.direction.dot(tangent) * tangent);
+            let facing = if towards_ray.near_zero() {
+                Onb::from_w(tangent).u
+            } else {
+                towards_ray.normalize()
+            };
+            let side = tangent.cross(facing);
+            let offset = if width > 0.0 {
+                (2.0 * (p - center).dot(side) / width).clamp(-1.0, 1.0)
+            } else {
+                0.0
+            };
+            let v = 0.5 + 0.5 * offset;
+            // Look for the next hit behind a cut out one
+            if !self.material.is_opaque_at(r, t, u, v, p) {
+                z_min = z.next_up();
+                continue;
+            }
+            let theta = offset.asin();
+            rec.t = t;
+            rec.p = p;
+            (rec.u, rec.v) = (u, v);
+            (rec.dpdu, rec.dpdv) = (dpdu, width * side);
+            rec.set_face_normal(r, theta.cos() * facing + theta.sin() * side);
+            rec.material = &self.material;
+            return true;
+        }
+    }
+
+    fn bounding_box(&self, _time_frame: (f64, f64)) -> Option<Aabb> {
+        // The curve stays in the hull of its control points
+        let half_width = 0.5 * self.width(self.u.0).max(self.width(self.u.1));
+        let mut bbox = Aabb::new(self.points[0], self.points[0]);
+        for p in &self.points[1..] {
+            bbox = Aabb::surronding_box(&bbox, &Aabb::new(*p, *p));
+        }
+        Some(bbox.padded(half_width))
+    }
+}
+
+// Hair or fur: many curves sharing a material, under a bounding volume
+// hierarchy of their own. u goes along each curve and v across it.
+pub struct Curves {
+    segments: BvhNode,
+}
+
+impl Curves {
+    pub fn new(curves: &[Curve], material: Material, rng: &mut dyn RngCore) -> Self {
+        let material = Arc::new(material);
+        let mut segments: Vec<Arc<dyn Hittable + Send + Sync>> = Vec::new();
+        for curve in curves {
+            for i in 0..SEGMENTS_PER_CURVE {
+                let u = (
+                    i as f64 / SEGMENTS_PER_CURVE as f64,
+                    (i + 1) as f64 / SEGMENTS_PER_CURVE as f64,
+                );
+                segments.push(Arc::new(Segment {
+                    points: sub_curve(&curve.points, u.0, u.1),
+                    u,
+                    widths: curve.widths,
+                    material: material.clone(),
+                }));
+            }
+        }
+        assert!(!segments.is_empty(), "Curves need at least one curve");
+        Curves {
+            segments: BvhNode::new(&segments, rng, 0, segments.len(), &(0.0, 0.0)),
+        }
+    }
+}
+
+impl Hittable for Curves {
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        self.segments.hit(r, t_min, t_max, rec)
+    }
+
+    fn bounding_box(&self, time_frame: (f64, f64)) -> Option<Aabb> {
+        self.segments.bounding_box(time_frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Curve, Curves, Segment};
+    use crate::raytracer::hittable::bezier::bezier;
+    use crate::{
+        raytracer::{
+            hittable::{
+                material::{Cutout, Material},
+                texture::Texture,
+            },
+            HitRecord, Hittable, Ray,
+        },
+        vec3::{Color, Point3, Vec3},
+    };
+    use rand::thread_rng;
+    use std::sync::Arc;
+
+    #[test]
+    fn hit() {
+        // Straight fiber along x, thinning from 0.2 to 0
+        let straight = [0., 1., 2., 3.].map(|x| Point3::new(x, 0., 0.));
+        let fiber = Curves::new(
+            &[Curve::new(straight, 0.2, 0.0)],
+            Material::None,
+            &mut thread_rng(),
+        );
+        let mut rec = HitRecord::new();
+
+        let ray = Ray::new(Point3::new(0.3, 0.085, 5.), Vec3::new(0., 0., -1.), 0.0);
+        assert!(fiber.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 5.0).abs() < 1e-9);
+        assert!((rec.u - 0.1).abs() < 1e-9);
+        assert!(rec.v < 0.1);
+        assert!(rec.front_face);
+        // The normal leans towards the edge, like that of a tube
+        assert!(rec.normal.y > 0.5 && rec.normal.z > 0.0);
+        assert!(rec.dpdu.normalize().dot(Vec3::new(1., 0., 0.)) > 1.0 - 1e-9);
+
+        // Past the tapering edge
+        let ray = Ray::new(Point3::new(2.7, 0.05, 5.), Vec3::new(0., 0., -1.), 0.0);
+        assert!(!fiber.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        let ray = Ray::new(Point3::new(0.3, 0.11, 5.), Vec3::new(0., 0., -1.), 0.0);
+        assert!(!fiber.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        let ray = Ray::new(Point3::new(0.3, 0.0, 5.), Vec3::new(0., 0., -1.), 0.0);
+        assert!(!fiber.hit(&ray, 0.001, 4.0, &mut rec));
+    }
+
+    #[test]
+    fn curved_hits() {
+        let p = [
+            Point3::new(0., 0., 0.),
+            Point3::new(0., 2., 1.),
+            Point3::new(2., 2., -1.),
+            Point3::new(2., 0., 0.),
+        ];
+        let curve = Curves::new(
+            &[Curve::new(p, 0.05, 0.05)],
+            Material::None,
+            &mut thread_rng(),
+        );
+        let mut rec = HitRecord::new();
+        for u in [0.1, 0.35, 0.5, 0.8] {
+            // From any side onto the point of the curve at u
+            let target = bezier(&p, u);
+            for origin in [Point3::new(1., 1., 5.), Point3::new(-3., 4., -2.)] {
+                let ray = Ray::new(origin, target - origin, 0.0);
+                assert!(curve.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+                assert!((rec.p - target).length() < 0.03, "{:?}", rec.p);
+                assert!((rec.u - u).abs() < 0.02);
+            }
+        }
+        // Two fibers, the nearest one first
+        let behind = p.map(|p| p + Vec3::new(0., 0., -1.));
+        let both = Curves::new(
+            &[Curve::new(behind, 0.05, 0.05), Curve::new(p, 0.05, 0.05)],
+            Material::None,
+            &mut thread_rng(),
+        );
+        let target = bezier(&p, 0.1);
+        let ray = Ray::new(target + Vec3::new(0., 0., 5.), Vec3::new(0., 0., -1.), 0.0);
+        assert!(both.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 5.0).abs() < 0.03);
+    }
+
+    #[test]
+    fn holes() {
+        // Folded back over itself along z, crossing x = 0 at z = 0.3 and 2.7,
+        // with a hole around the nearest crossing
+        let fold = Segment {
+            points: [
+                Point3::new(-1., 0., 0.),
+                Point3::new(3., 0., 1.),
+                Point3::new(3., 0., 2.),
+                Point3::new(-1., 0., 3.),
+            ],
+            u: (0.0, 1.0),
+            widths: (0.2, 0.2),
+            material: Arc::new(Cutout::new(
+                Material::None,
+                Texture::checker(Color::ones(), Color::zeros(), 2.0),
+            )),
+        };
+        let mut rec = HitRecord::new();
+        let ray = Ray::new(Point3::new(0., 0., 5.), Vec3::new(0., 0., -1.), 0.0);
+        assert!(fold.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.p.z - 0.3).abs() < 0.03, "{:?}", rec.p);
+        assert!(!fold.hit(&ray, 0.001, 4.0, &mut rec));
+    }
+}
@@ -0,0 +1,158 @@
+use super::{
+    aabb::Aabb,
+    disk::{circle_extent, Disk},
+    material::Material,
+    HitRecord, Hittable,
+};
+use crate::{
+    raytracer::Ray,
+    vec3::{Onb, Point3, Vec3},
+};
+use std::f64::consts::PI;
+
+// Cylinder of `radius` going from `base` to `base + axis`, closed by two
+// disks. On its side u goes around the axis and v up along it.
+pub struct Cylinder {
+    base: Point3,
+    frame: Onb,
+    height: f64,
+    radius: f64,
+    material: Material,
+    caps: [Disk; 2],
+}
+
+impl Cylinder {
+    pub fn new(base: Point3, axis: Vec3, radius: f64, material: Material) -> Self {
+        let caps = [
+            Disk::new(base, -axis, radius, material.clone()),
+            Disk::new(base + axis, axis, radius, material.clone()),
+        ];
+        Cylinder {
+            base,
+            frame: Onb::from_w(axis),
+            height: axis.length(),
+            radius,
+            material,
+            caps,
+        }
+    }
+    fn hit_side<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        let origin = self.frame.to_local(r.origin - self.base);
+        let direction = self.frame.to_local(r.direction);
+
+        let a = direction.x * direction.x + direction.y * direction.y;
+        if a == 0.0 {
+            return false;
+        }
+        let half_b = origin.x * direction.x + origin.y * direction.y;
+        let c = origin.x * origin.x + origin.y * origin.y - self.radius * self.radius;
+        let discriminant = half_b * half_b - a * c;
+        if discriminant < 0.0 {
+            return false;
+        }
+        let sqrtd = discriminant.sqrt();
+
+        // Nearest root first, the far one showing through cutout holes
+        for root in [(-half_b - sqrtd) / a, (-half_b + sqrtd) / a] {
+            if root < t_min || t_max < root {
+                continue;
+            }
+            let local = origin + root * direction;
+            if local.z < 0.0 || local.z > self.height {
+                continue;
+            }
+            let phi = local.y.atan2(local.x).rem_euclid(2.0 * PI);
+            let (u, v) = (phi / (2.0 * PI), local.z / self.height);
+            let p = r.at(root);
+            if !self.material.is_opaque_at(r, root, u, v, p) {
+                continue;
+            }
+            rec.t = root;
+            rec.p = p;
+            (rec.u, rec.v) = (u, v);
+            rec.dpdu = self
+                .frame
+                .local(2.0 * PI * Vec3::new(-local.y, local.x, 0.0));
+            rec.dpdv = self.height * self.frame.w;
+            let out_normal = self.frame.local(Vec3::new(local.x, local.y, 0.0)) / self.radius;
+            rec.set_face_normal(r, out_normal);
+            rec.material = &self.material;
+            return true;
+        }
+        false
+    }
+}
+
+impl Hittable for Cylinder {
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        let mut closest = t_max;
+        let mut hit_any = false;
+        if self.hit_side(r, t_min, closest, rec) {
+            hit_any = true;
+            closest = rec.t;
+        }
+        for cap in &self.caps {
+            if cap.hit(r, t_min, closest, rec) {
+                hit_any = true;
+                closest = rec.t;
+            }
+        }
+        hit_any
+    }
+
+    fn bounding_box(&self, _time_frame: (f64, f64)) -> Option<Aabb> {
+        let extent = circle_extent(self.frame.w, self.radius);
+        let top = self.base + self.height * self.frame.w;
+        Some(Aabb::surronding_box(
+            &Aabb::new(self.base - extent, self.base + extent),
+            &Aabb::new(top - extent, top + extent),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cylinder;
+    use crate::{
+        raytracer::{hittable::material::Material, HitRecord, Hittable, Ray},
+        vec3::{Point3, Vec3},
+    };
+
+    #[test]
+    fn hit() {
+        let cylinder = Cylinder::new(Point3::zeros(), 2.0 * Vec3::up(), 1., Material::None);
+        let mut rec = HitRecord::new();
+
+        // Side, from outside then from inside
+        let ray = Ray::new(Point3::new(-3., 0.5, 0.), Vec3::new(1., 0., 0.), 0.0);
+        assert!(cylinder.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!(rec.t, 2.0);
+        assert_eq!(rec.normal, Vec3::new(-1., 0., 0.));
+        assert!(rec.front_face);
+        assert_eq!(rec.v, 0.25);
+        let ray = Ray::new(Point3::new(0., 0.5, 0.), Vec3::new(1., 0., 0.), 0.0);
+        assert!(cylinder.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert!(!rec.front_face);
+
+        // Caps
+        let ray = Ray::new(Point3::new(0.5, 5., 0.), -Vec3::up(), 0.0);
+        assert!(cylinder.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!((rec.t, rec.normal), (3.0, Vec3::up()));
+        let ray = Ray::new(Point3::new(0.5, 1., 0.), -Vec3::up(), 0.0);
+        assert!(cylinder.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!((rec.t, rec.normal), (1.0, Vec3::up()));
+        assert!(!rec.front_face);
+
+        // Past the top
+        let ray = Ray::new(Point3::new(-3., 2.5, 0.), Vec3::new(1., 0., 0.), 0.0);
+        assert!(!cylinder.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+    }
+
+    #[test]
+    fn bounding_box() {
+        let cylinder = Cylinder::new(Point3::zeros(), 2.0 * Vec3::up(), 1., Material::None);
+        let bbox = cylinder.bounding_box((0.0, 0.0)).unwrap();
+        assert!((bbox.minimum - Vec3::new(-1., 0., -1.)).length() < 1e-12);
+        assert!((bbox.maximum - Vec3::new(1., 2., 1.)).length() < 1e-12);
+    }
+}
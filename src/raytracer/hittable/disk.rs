@@ -0,0 +1,131 @@
+use super::{aabb::Aabb, light::Light, material::Material, planar_pdf_value, HitRecord, Hittable};
+use crate::{
+    raytracer::Ray,
+    vec3::{Onb, Point3, Vec3},
+};
+use rand::RngCore;
+use std::f64::consts::PI;
+
+// Disk of `radius` around `center`, facing `normal`. u goes around the
+// center and v out to the rim.
+pub struct Disk {
+    center: Point3,
+    frame: Onb,
+    radius: f64,
+    material: Material,
+}
+
+impl Disk {
+    pub fn new(center: Point3, normal: Vec3, radius: f64, material: Material) -> Self {
+        Disk {
+            center,
+            frame: Onb::from_w(normal),
+            radius,
+            material,
+        }
+    }
+    fn area(&self) -> f64 {
+        PI * self.radius * self.radius
+    }
+}
+
+// Extent along each world axis of a circle of `radius` facing `normal`
+pub(super) fn circle_extent(normal: Vec3, radius: f64) -> Vec3 {
+    let normal = normal.normalize();
+    radius
+        * Vec3::new(
+            (1.0 - normal.x * normal.x).max(0.0).sqrt(),
+            (1.0 - normal.y * normal.y).max(0.0).sqrt(),
+            (1.0 - normal.z * normal.z).max(0.0).sqrt(),
+        )
+}
+
+impl Hittable for Disk {
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        let denominator = r.direction.dot(self.frame.w);
+        if denominator == 0.0 {
+            return false;
+        }
+        let t = (self.center - r.origin).dot(self.frame.w) / denominator;
+        if !(t_min..t_max).contains(&t) {
+            return false;
+        }
+        let p = r.at(t);
+        let local = self.frame.to_local(p - self.center);
+        let distance = local.x.hypot(local.y);
+        if distance > self.radius {
+            return false;
+        }
+        let phi = local.y.atan2(local.x).rem_euclid(2.0 * PI);
+        let (u, v) = (phi / (2.0 * PI), distance / self.radius);
+        if !self.material.is_opaque_at(r, t, u, v, p) {
+            return false;
+        }
+        rec.t = t;
+        rec.p = p;
+        (rec.u, rec.v) = (u, v);
+        rec.dpdu = self
+            .frame
+            .local(2.0 * PI * Vec3::new(-local.y, local.x, 0.0));
+        rec.dpdv = self
+            .frame
+            .local(self.radius * Vec3::new(phi.cos(), phi.sin(), 0.0));
+        rec.set_face_normal(r, self.frame.w);
+        rec.material = &self.material;
+        true
+    }
+
+    fn bounding_box(&self, _time_frame: (f64, f64)) -> Option<Aabb> {
+        let extent = circle_extent(self.frame.w, self.radius);
+        Some(Aabb::new(self.center - extent, self.center + extent).with_volume())
+    }
+}
+
+impl Light for Disk {
+    fn pdf_value(&self, r: &Ray) -> f64 {
+        planar_pdf_value(self, self.area(), r)
+    }
+
+    fn random(&self, origin: Point3, _time: f64, rng: &mut dyn RngCore) -> Vec3 {
+        let offset = self.radius * Vec3::rand_in_disk(rng);
+        self.center + self.frame.local(offset) - origin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Disk;
+    use crate::{
+        raytracer::{hittable::material::Material, HitRecord, Hittable, Ray},
+        vec3::{Point3, Vec3},
+    };
+
+    #[test]
+    fn hit() {
+        let disk = Disk::new(
+            Point3::new(0., 0., -2.),
+            Vec3::new(0., 0., 1.),
+            1.,
+            Material::None,
+        );
+        let mut rec = HitRecord::new();
+
+        let ray = Ray::new(Point3::new(0.5, 0., 0.), Vec3::new(0., 0., -1.), 0.0);
+        assert!(disk.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!(rec.t, 2.0);
+        assert!(rec.front_face);
+        assert_eq!((rec.u, rec.v), (0.0, 0.5));
+
+        let ray = Ray::new(Point3::new(0.8, 0.8, 0.), Vec3::new(0., 0., -1.), 0.0);
+        assert!(!disk.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+    }
+
+    #[test]
+    fn tilted_bounding_box() {
+        let normal = Vec3::new(1., 1., 0.).normalize();
+        let disk = Disk::new(Point3::zeros(), normal, 2., Material::None);
+        let bbox = disk.bounding_box((0.0, 0.0)).unwrap();
+        let half = 2.0 * 0.5f64.sqrt();
+        assert!((bbox.maximum - Vec3::new(half, half, 2.0)).length() < 1e-3);
+    }
+}
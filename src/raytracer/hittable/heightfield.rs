@@ -0,0 +1,415 @@
+use super::{aabb::Aabb, material::Material, mesh::hit_triangle, HitRecord, Hittable};
+use crate::{
+    raytracer::Ray,
+    vec3::{Color, Point3, Vec3},
+};
+use std::{fs, io, path::Path};
+
+// Terrain of heights sampled on a regular grid over the xz plane, from
+// `origin` across `extent`, each cell split into two triangles. Normals are
+// interpolated between samples and uv are the fractions of the extent.
+pub struct Heightfield {
+    origin: Point3,
+    extent: (f64, f64),
+    resolution: (usize, usize),
+    // Sizes of a cell along x and z
+    cell: (f64, f64),
+    // Above `origin`, row by row along x
+    heights: Vec<f64>,
+    normals: Vec<Vec3>,
+    bbox: Aabb,
+    material: Material,
+}
+
+impl Heightfield {
+    // `heights` holds `resolution.0` samples along x for each of the
+    // `resolution.1` rows along z
+    pub fn new(
+        origin: Point3,
+        extent: (f64, f64),
+        resolution: (usize, usize),
+        heights: Vec<f64>,
+        material: Material,
+    ) -> Self {
+        let (nx, nz) = resolution;
+        assert!(nx >= 2 && nz >= 2, "A heightfield needs 2 samples per axis");
+        assert_eq!(heights.len(), nx * nz);
+        let cell = (extent.0 / (nx - 1) as f64, extent.1 / (nz - 1) as f64);
+
+        // Central differences, one-sided on the borders
+        let mut normals = Vec::with_capacity(nx * nz);
+        for j in 0..nz {
+            for i in 0..nx {
+                let (i0, i1) = (i.saturating_sub(1), (i + 1).min(nx - 1));
+                let (j0, j1) = (j.saturating_sub(1), (j + 1).min(nz - 1));
+                let dhdx =
+                    (heights[j * nx + i1] - heights[j * nx + i0]) / ((i1 - i0) as f64 * cell.0);
+                let dhdz =
+                    (heights[j1 * nx + i] - heights[j0 * nx + i]) / ((j1 - j0) as f64 * cell.1);
+                normals.push(Vec3::new(-dhdx, 1.0, -dhdz).normalize());
+            }
+        }
+
+        let lowest = heights.iter().cloned().fold(f64::INFINITY, f64::min);
+        let highest = heights.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let bbox = Aabb::new(
+            origin + Vec3::new(0.0, lowest, 0.0),
+            origin + Vec3::new(extent.0, highest, extent.1),
+        )
+        .with_volume();
+        Heightfield {
+            origin,
+            extent,
+            resolution,
+            cell,
+            heights,
+            normals,
+            bbox,
+            material,
+        }
+    }
+    // Samples `height`, a function of the world x and z, e.g. some noise
+    pub fn from_fn(
+        origin: Point3,
+        extent: (f64, f64),
+        resolution: (usize, usize),
+        height: impl Fn(f64, f64) -> f64,
+        material: Material,
+    ) -> Self {
+        let (nx, nz) = resolution;
+        let mut heights = Vec::with_capacity(nx * nz);
+        for j in 0..nz {
+            for i in 0..nx {
+                let x = origin.x + extent.0 * i as f64 / (nx - 1).max(1) as f64;
+                let z = origin.z + extent.1 * j as f64 / (nz - 1).max(1) as f64;
+                heights.push(height(x, z));
+            }
+        }
+        Self::new(origin, extent, resolution, heights, material)
+    }
+    // Grayscale PGM or PPM image, white being `height` above `origin`. Its
+    // columns go along x and its rows along z.
+    pub fn from_image(
+        path: impl AsRef<Path>,
+        origin: Point3,
+        extent: (f64, f64),
+        height: f64,
+        material: Material,
+    ) -> io::Result<Self> {
+        let (width, rows, levels) = read_pnm(&fs::read(path)?)?;
+        let heights = levels.into_iter().map(|level| height * level).collect();
+        Ok(Self::new(origin, extent, (width, rows), heights, material))
+    }
+    // Height of the surface at the world x and z, if it is above them
+    pub fn height_at(&self, x: f64, z: f64) -> Option<f64> {
+        let fx = (x - self.origin.x) / self.cell.0;
+        let fz = (z - self.origin.z) / self.cell.1;
+        let (nx, nz) = self.resolution;
+        if !(0.0..=(nx - 1) as f64).contains(&fx) || !(0.0..=(nz - 1) as f64).contains(&fz) {
+            return None;
+        }
+        let i = (fx.floor() as usize).min(nx - 2);
+        let j = (fz.floor() as usize).min(nz - 2);
+        let (fx, fz) = (fx - i as f64, fz - j as f64);
+        let h = |di: usize, dj: usize| self.heights[(j + dj) * nx + i + di];
+        // Same split along the diagonal as the triangles
+        let height = if fx >= fz {
+            h(0, 0) + fx * (h(1, 0) - h(0, 0)) + fz * (h(1, 1) - h(1, 0))
+        } else {
+            h(0, 0) + fz * (h(0, 1) - h(0, 0)) + fx * (h(1, 1) - h(0, 1))
+        };
+        Some(self.origin.y + height)
+    }
+    fn vertex(&self, i: usize, j: usize) -> Point3 {
+        self.origin
+            + Vec3::new(
+                i as f64 * self.cell.0,
+                self.heights[j * self.resolution.0 + i],
+                j as f64 * self.cell.1,
+            )
+    }
+    fn hit_cell<'a>(
+        &'a self,
+        i: usize,
+        j: usize,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord<'a>,
+    ) -> bool {
+        let nx = self.resolution.0;
+        let mut closest = t_max;
+        let mut hit_any = false;
+        for corners in [[(0, 0), (1, 0), (1, 1)], [(0, 0), (1, 1), (0, 1)]] {
+            let [a, b, c] = corners.map(|(di, dj)| (i + di, j + dj));
+            let vertices = [a, b, c].map(|(i, j)| self.vertex(i, j));
+            let Some((t, beta, gamma)) =
+                hit_triangle(r, vertices[0], vertices[1], vertices[2], t_min, closest)
+            else {
+                continue;
+            };
+            let p = r.at(t);
+            let u = (p.x - self.origin.x) / self.extent.0;
+            let v = (p.z - self.origin.z) / self.extent.1;
+            if !self.material.is_opaque_at(r, t, u, v, p) {
+                continue;
+            }
+            let normal = |(i, j): (usize, usize)| self.normals[j * nx + i];
+            let out_normal =
+                ((1.0 - beta - gamma) * normal(a) + beta * normal(b) + gamma * normal(c))
+                    .normalize();
+            // Slopes of the triangle's plane
+            let face = (vertices[1] - vertices[0]).cross(vertices[2] - vertices[0]);
+            let (dhdx, dhdz) = (-face.x / face.y, -face.z / face.y);
+            rec.t = t;
+            rec.p = p;
+            (rec.u, rec.v) = (u, v);
+            rec.dpdu = self.extent.0 * Vec3::new(1.0, dhdx, 0.0);
+            rec.dpdv = self.extent.1 * Vec3::new(0.0, dhdz, 1.0);
+            rec.set_face_normal(r, out_normal);
+            rec.material = &self.material;
+            closest = t;
+            hit_any = true;
+        }
+        hit_any
+    }
+}
+
+impl Hittable for Heightfield {
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        let Some((t_enter, t_exit)) = self.bbox.clip(r, t_min, t_max) else {
+            return false;
+        };
+        let (nx, nz) = self.resolution;
+        let (nx, nz) = (nx as isize, nz as isize);
+        let p = r.at(t_enter);
+        let mut i = (((p.x - self.origin.x) / self.cell.0).floor() as isize).clamp(0, nx - 2);
+        let mut j = (((p.z - self.origin.z) / self.cell.1).floor() as isize).clamp(0, nz - 2);
+
+        // Walk through the cells below the ray in order (grid DDA), keeping
+        // the distances to the next cell boundaries along x and z
+        let axis = |cell: isize, size: f64, origin: f64, ray_origin: f64, direction: f64| {
+            if direction > 0.0 {
+                let boundary = origin + (cell + 1) as f64 * size;
+                (1, (boundary - ray_origin) / direction, size / direction)
+            } else if direction < 0.0 {
+                let boundary = origin + cell as f64 * size;
+                (-1, (boundary - ray_origin) / direction, -size / direction)
+            } else {
+                (0, f64::INFINITY, f64::INFINITY)
+            }
+        };
+        let (step_i, mut t_next_x, delta_x) =
+            axis(i, self.cell.0, self.origin.x, r.origin.x, r.direction.x);
+        let (step_j, mut t_next_z, delta_z) =
+            axis(j, self.cell.1, self.origin.z, r.origin.z, r.direction.z);
+
+        let mut t_cell = t_enter;
+        loop {
+            let t_leave = t_next_x.min(t_next_z).min(t_exit);
+            // Skip cells the ray passes entirely above or below
+            let (y0, y1) = (r.at(t_cell).y, r.at(t_leave).y);
+            let corners = [(0, 0), (1, 0), (0, 1), (1, 1)]
+                .map(|(di, dj)| self.heights[(j + dj) as usize * nx as usize + (i + di) as usize]);
+            let lowest = self.origin.y + corners.iter().cloned().fold(f64::INFINITY, f64::min);
+            let highest = self.origin.y + corners.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+            if y0.max(y1) >= lowest
+                && y0.min(y1) <= highest
+                && self.hit_cell(i as usize, j as usize, r, t_min, t_max, rec)
+            {
+                return true;
+            }
+            if t_leave >= t_exit {
+                return false;
+            }
+            if t_next_x < t_next_z {
+                i += step_i;
+                t_next_x += delta_x;
+            } else {
+                j += step_j;
+                t_next_z += delta_z;
+            }
+            if !(0..nx - 1).contains(&i) || !(0..nz - 1).contains(&j) {
+                return false;
+            }
+            t_cell = t_leave;
+        }
+    }
+
+    fn bounding_box(&self, _time_frame: (f64, f64)) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+}
+
+// Width, height and gray levels in [0, 1] of a binary or plain PGM or PPM
+// image, row by row from the top
+fn read_pnm(bytes: &[u8]) -> io::Result<(usize, usize, Vec<f64>)> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+
+    // Header fields are separated by whitespace and comments
+    let mut position = 0;
+    let mut next_token = |bytes: &[u8]| -> io::Result<String> {
+        loop {
+            match bytes.get(position) {
+                Some(b'#') => {
+                    while bytes.get(position).is_some_and(|&b| b != b'\n') {
+                        position += 1;
+                    }
+                }
+                Some(b) if b.is_ascii_whitespace() => position += 1,
+                Some(_) => break,
+                None => return Err(invalid("Truncated image")),
+            }
+        }
+        let start = position;
+        while bytes
+            .get(position)
+            .is_some_and(|b| !b.is_ascii_whitespace())
+        {
+            position += 1;
+        }
+        Ok(String::from_utf8_lossy(&bytes[start..position]).into_owned())
+    };
+    let number = |token: String| -> io::Result<usize> {
+        token.parse().map_err(|_| invalid("Invalid image header"))
+    };
+
+    let magic = next_token(bytes)?;
+    let (channels, binary) = match magic.as_str() {
+        "P2" => (1, false),
+        "P3" => (3, false),
+        "P5" => (1, true),
+        "P6" => (3, true),
+        _ => return Err(invalid("Not a PGM or PPM image")),
+    };
+    let width = number(next_token(bytes)?)?;
+    let height = number(next_token(bytes)?)?;
+    let max_value = number(next_token(bytes)?)?;
+    if width < 2 || height < 2 || max_value == 0 || max_value > 65535 {
+        return Err(invalid("Invalid image header"));
+    }
+
+    let count = width * height * channels;
+    let samples: Vec<usize> = if binary {
+        // A single whitespace character separates the header from the data
+        let data = bytes.get(position + 1..).unwrap_or_default();
+        let size = if max_value < 256 { 1 } else { 2 };
+        if data.len() < count * size {
+            return Err(invalid("Truncated image"));
+        }
+        data.chunks(size)
+            .take(count)
+            .map(|c| c.iter().fold(0, |sample, &b| sample << 8 | b as usize))
+            .collect()
+    } else {
+        (0..count)
+            .map(|_| number(next_token(bytes)?))
+            .collect::<io::Result<_>>()?
+    };
+
+    let levels = samples
+        .chunks(channels)
+        .map(|sample| {
+            let level = |s: usize| s as f64 / max_value as f64;
+            if channels == 1 {
+                level(sample[0])
+            } else {
+                Color::new(level(sample[0]), level(sample[1]), level(sample[2])).luminance()
+            }
+        })
+        .collect();
+    Ok((width, height, levels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hit_triangle, read_pnm, Heightfield};
+    use crate::{
+        raytracer::{hittable::material::Material, HitRecord, Hittable, Ray},
+        vec3::{Point3, Vec3},
+    };
+    use rand::{thread_rng, Rng};
+
+    fn hills() -> Heightfield {
+        Heightfield::from_fn(
+            Point3::new(-4., -1., -4.),
+            (8., 8.),
+            (33, 17),
+            |x, z| (x * 1.3).sin() + (z * 0.7).cos(),
+            Material::None,
+        )
+    }
+
+    #[test]
+    fn image() {
+        let pgm = b"P2\n# corners\n3 2\n4\n0 1 2\n4 3 2\n";
+        let (width, height, levels) = read_pnm(pgm).unwrap();
+        assert_eq!((width, height), (3, 2));
+        assert_eq!(levels, vec![0.0, 0.25, 0.5, 1.0, 0.75, 0.5]);
+        let ppm = b"P6 2 2 255\n\xff\xff\xff\x00\x00\x00\x00\x00\x00\xff\xff\xff";
+        let (_, _, levels) = read_pnm(ppm).unwrap();
+        assert!((levels[0] - 1.0).abs() < 1e-12 && levels[1] == 0.0);
+        assert!(read_pnm(b"P5 2 2 255\n\x00").is_err());
+        assert!(read_pnm(b"GIF89a").is_err());
+    }
+
+    #[test]
+    fn hit() {
+        // Ramp rising by 1 along x
+        let ramp =
+            Heightfield::from_fn(Point3::zeros(), (4., 2.), (5, 3), |x, _| x, Material::None);
+        assert_eq!(ramp.height_at(2.5, 1.5), Some(2.5));
+        assert_eq!(ramp.height_at(5., 1.), None);
+
+        let mut rec = HitRecord::new();
+        let ray = Ray::new(Point3::new(2.5, 10., 1.5), -Vec3::up(), 0.0);
+        assert!(ramp.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 7.5).abs() < 1e-12);
+        assert!((rec.normal - Vec3::new(-1., 1., 0.).normalize()).length() < 1e-12);
+        assert!((rec.u - 0.625).abs() < 1e-12 && (rec.v - 0.75).abs() < 1e-12);
+        assert!(rec.dpdu.dot(rec.normal).abs() < 1e-12);
+        assert!(rec.dpdv.dot(rec.normal).abs() < 1e-12);
+
+        // Into the ramp from its side, then above it
+        let ray = Ray::new(Point3::new(-1., 0.5, 1.), Vec3::new(1., 0., 0.), 0.0);
+        assert!(ramp.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 1.5).abs() < 1e-12);
+        assert!(rec.front_face);
+        let ray = Ray::new(Point3::new(-1., 5., 1.), Vec3::new(1., 0., 0.), 0.0);
+        assert!(!ramp.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+    }
+
+    #[test]
+    fn matches_every_triangle() {
+        // Walking the grid finds the same hits as testing all triangles
+        let field = hills();
+        let (nx, nz) = field.resolution;
+        let mut rng = thread_rng();
+        for _ in 0..500 {
+            let origin = Point3::rand(&mut rng, -6.0, 6.0);
+            let target = Point3::new(rng.gen_range(-4.0..4.0), 0.0, rng.gen_range(-4.0..4.0));
+            let ray = Ray::new(origin, target - origin, 0.0);
+
+            let mut closest = f64::INFINITY;
+            for j in 0..nz - 1 {
+                for i in 0..nx - 1 {
+                    for [a, b, c] in [[(0, 0), (1, 0), (1, 1)], [(0, 0), (1, 1), (0, 1)]] {
+                        let [a, b, c] = [a, b, c].map(|(di, dj)| field.vertex(i + di, j + dj));
+                        if let Some((t, _, _)) = hit_triangle(&ray, a, b, c, 0.001, closest) {
+                            closest = t;
+                        }
+                    }
+                }
+            }
+
+            let mut rec = HitRecord::new();
+            if field.hit(&ray, 0.001, f64::INFINITY, &mut rec) {
+                assert!((rec.t - closest).abs() < 1e-9, "{} {}", rec.t, closest);
+                let height = field.height_at(rec.p.x, rec.p.z).unwrap();
+                assert!((rec.p.y - height).abs() < 1e-9);
+            } else {
+                assert_eq!(closest, f64::INFINITY);
+            }
+        }
+    }
+}
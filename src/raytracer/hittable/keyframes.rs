@@ -0,0 +1,51 @@
+use std::ops::{Add, Mul};
+
+// Keyframes, values given at times, sorted by time
+pub(super) fn sorted<T>(keyframes: Vec<(f64, T)>) -> Vec<(f64, T)> {
+    assert!(!keyframes.is_empty(), "Keyframes need at least one value");
+    let mut keyframes = keyframes;
+    keyframes.sort_by(|a, b| a.0.total_cmp(&b.0));
+    keyframes
+}
+
+// Value at `time` of keyframes sorted by time, blended by `lerp` between them
+// and held beyond them
+pub(super) fn interpolate_with<T: Copy>(
+    keyframes: &[(f64, T)],
+    time: f64,
+    lerp: impl Fn(T, T, f64) -> T,
+) -> T {
+    let next = keyframes.partition_point(|&(t, _)| t <= time);
+    if next == 0 {
+        return keyframes[0].1;
+    }
+    if next == keyframes.len() {
+        return keyframes[next - 1].1;
+    }
+    let ((t0, a), (t1, b)) = (keyframes[next - 1], keyframes[next]);
+    lerp(a, b, (time - t0) / (t1 - t0))
+}
+
+// Same, linearly interpolated
+pub(super) fn interpolate<T>(keyframes: &[(f64, T)], time: f64) -> T
+where
+    T: Copy + Add<Output = T> + Mul<f64, Output = T>,
+{
+    interpolate_with(keyframes, time, |a, b, s| a * (1.0 - s) + b * s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{interpolate, sorted};
+
+    #[test]
+    fn interpolation() {
+        let keyframes = sorted(vec![(2.0, 4.0), (0.0, 0.0), (1.0, 1.0)]);
+        assert_eq!(keyframes, vec![(0.0, 0.0), (1.0, 1.0), (2.0, 4.0)]);
+        assert_eq!(interpolate(&keyframes, -1.0), 0.0);
+        assert_eq!(interpolate(&keyframes, 0.5), 0.5);
+        assert_eq!(interpolate(&keyframes, 1.5), 2.5);
+        assert_eq!(interpolate(&keyframes, 3.0), 4.0);
+        assert_eq!(interpolate(&[(1.0, 2.0)], 0.0), 2.0);
+    }
+}
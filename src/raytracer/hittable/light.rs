@@ -0,0 +1,79 @@
+use crate::{
+    raytracer::Ray,
+    vec3::{Point3, Vec3},
+};
+use rand::{Rng, RngCore};
+use std::sync::Arc;
+
+// Object whose directions can be sampled, to light points directly. Lights
+// should also be among the objects of the scene to be seen.
+pub trait Light {
+    // Solid angle density of `random` for the direction of `r`
+    fn pdf_value(&self, r: &Ray) -> f64;
+    // Random direction from `origin` towards the light
+    fn random(&self, origin: Point3, time: f64, rng: &mut dyn RngCore) -> Vec3;
+}
+
+// Lights picked uniformly at random
+#[derive(Clone, Default)]
+pub struct LightList {
+    pub lights: Vec<Arc<dyn Light + Send + Sync>>,
+}
+
+impl LightList {
+    pub fn new() -> Self {
+        LightList { lights: Vec::new() }
+    }
+    pub fn add(&mut self, light: Arc<dyn Light + Send + Sync>) {
+        self.lights.push(light);
+    }
+}
+
+impl Light for LightList {
+    fn pdf_value(&self, r: &Ray) -> f64 {
+        let weight = 1.0 / self.lights.len() as f64;
+        self.lights
+            .iter()
+            .map(|light| weight * light.pdf_value(r))
+            .sum()
+    }
+    fn random(&self, origin: Point3, time: f64, rng: &mut dyn RngCore) -> Vec3 {
+        let index = rng.gen_range(0..self.lights.len());
+        self.lights[index].random(origin, time, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Light, LightList};
+    use crate::{
+        raytracer::{hittable::material::Material, AaRect, Ray, Sphere},
+        vec3::{Point3, Vec3},
+    };
+    use rand::thread_rng;
+    use std::sync::Arc;
+
+    #[test]
+    fn mixture() {
+        let sphere = Arc::new(Sphere::new(Point3::new(0., 0., -3.), 1., Material::None));
+        let rect = Arc::new(AaRect::xz(-1., 1., -1., 1., 3., Material::None));
+        let mut lights = LightList::new();
+        lights.add(sphere.clone());
+        lights.add(rect.clone());
+
+        // Every sample heads for one of the lights, with half its density
+        let mut rng = thread_rng();
+        for _ in 0..1000 {
+            let r = Ray::new(
+                Point3::zeros(),
+                lights.random(Point3::zeros(), 0.0, &mut rng),
+                0.0,
+            );
+            let (a, b) = (sphere.pdf_value(&r), rect.pdf_value(&r));
+            assert!(a > 0.0 || b > 0.0);
+            assert!((lights.pdf_value(&r) - 0.5 * (a + b)).abs() < 1e-12);
+        }
+        let away = Ray::new(Point3::zeros(), Vec3::new(1., 0., 0.), 0.0);
+        assert_eq!(lights.pdf_value(&away), 0.0);
+    }
+}
@@ -1,32 +1,172 @@
+pub(crate) mod coated;
+pub(crate) mod cutout;
+pub(crate) mod hair;
+pub(crate) mod ior;
+pub(crate) mod microfacet;
+pub(crate) mod principled;
+
 use super::HitRecord;
 use crate::{
     raytracer::Ray,
-    vec3::{Color, Vec3},
+    vec3::{Color, Onb, Point3, Vec3},
 };
-use rand::{rngs::ThreadRng, Rng};
+use rand::{Rng, RngCore};
+use std::{f64::consts::PI, fmt::Debug, sync::Arc};
 
-#[derive(Debug, Copy, Clone)]
+pub use coated::Coated;
+pub use cutout::Cutout;
+pub use hair::Hair;
+pub use ior::Ior;
+pub use microfacet::{Conductor, RoughDielectric};
+pub use principled::{Principled, PrincipledBuilder};
+
+// Principled carries a texture per parameter and dwarfs the other variants,
+// which is fine as long as hit records only borrow them
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
 pub enum Material {
     Lambertian(Lambertian),
     Metal(Metal),
     Dielectric(Dielectric),
+    Conductor(Conductor),
+    RoughDielectric(RoughDielectric),
+    Principled(Principled),
+    Coated(Coated),
+    Cutout(Cutout),
+    Hair(Hair),
+    DiffuseLight(DiffuseLight),
+    Custom(Arc<dyn Scatter>),
     None,
 }
 
+// Material of hit records before anything is hit
+pub static NONE: Material = Material::None;
+
+// Materials defined outside of this module, plugged in through
+// `Material::custom`. The built-in variants keep their direct dispatch.
+pub trait Scatter: Debug + Send + Sync {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &mut HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<ScatterRecord>;
+    // See `Material::eval`, only needed for lobes that are not delta ones
+    fn eval(&self, _rec: &HitRecord, _wi: Vec3, _wo: Vec3) -> Color {
+        Color::zeros()
+    }
+    fn pdf(&self, _rec: &HitRecord, _wi: Vec3, _wo: Vec3) -> f64 {
+        0.0
+    }
+    fn emitted(&self, _rec: &HitRecord) -> Color {
+        Color::zeros()
+    }
+}
+
+// Sampled scattering event
+pub struct ScatterRecord {
+    // BSDF times cosine over pdf, or the lobe weight for delta lobes
+    pub attenuation: Color,
+    pub ray: Ray,
+    // Solid angle density of the scattered direction, meaningless for delta lobes
+    pub pdf: f64,
+    // Perfectly specular lobes cannot be evaluated nor hit by light sampling
+    pub is_delta: bool,
+}
+
+impl ScatterRecord {
+    pub fn new(attenuation: Color, ray: Ray, pdf: f64) -> Self {
+        ScatterRecord {
+            attenuation,
+            ray,
+            pdf,
+            is_delta: false,
+        }
+    }
+    pub fn delta(attenuation: Color, ray: Ray) -> Self {
+        ScatterRecord {
+            attenuation,
+            ray,
+            pdf: 0.0,
+            is_delta: true,
+        }
+    }
+}
+
 impl Material {
+    pub fn custom(material: impl Scatter + 'static) -> Self {
+        Material::Custom(Arc::new(material))
+    }
     pub fn scatter(
         &self,
         r_in: &Ray,
         rec: &mut HitRecord,
-        rng: &mut ThreadRng,
-    ) -> Option<(Vec3, Ray)> {
+        rng: &mut dyn RngCore,
+    ) -> Option<ScatterRecord> {
         match self {
             Material::Lambertian(l) => l.scatter(r_in, rec, rng),
             Material::Metal(m) => m.scatter(r_in, rec, rng),
             Material::Dielectric(d) => d.scatter(r_in, rec, rng),
+            Material::Conductor(c) => c.scatter(r_in, rec, rng),
+            Material::RoughDielectric(d) => d.scatter(r_in, rec, rng),
+            Material::Principled(p) => p.scatter(r_in, rec, rng),
+            Material::Coated(c) => c.scatter(r_in, rec, rng),
+            Material::Cutout(c) => c.scatter(r_in, rec, rng),
+            Material::Hair(h) => h.scatter(r_in, rec, rng),
+            Material::Custom(c) => c.scatter(r_in, rec, rng),
             _ => Option::None,
         }
     }
+    // BSDF times the cosine term, for light arriving from `wi` and leaving
+    // towards `wo` (both pointing away from the surface). Delta lobes cannot
+    // be evaluated and return zero.
+    pub fn eval(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> Color {
+        match self {
+            Material::Lambertian(l) => l.eval(rec, wi, wo),
+            Material::Metal(m) => m.eval(rec, wi, wo),
+            Material::Conductor(c) => c.eval(rec, wi, wo),
+            Material::RoughDielectric(d) => d.eval(rec, wi, wo),
+            Material::Principled(p) => p.eval(rec, wi, wo),
+            Material::Coated(c) => c.eval(rec, wi, wo),
+            Material::Cutout(c) => c.eval(rec, wi, wo),
+            Material::Hair(h) => h.eval(rec, wi, wo),
+            Material::Custom(c) => c.eval(rec, wi, wo),
+            _ => Color::zeros(),
+        }
+    }
+    // Solid angle density with which `scatter` picks `wi` given `wo`
+    pub fn pdf(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> f64 {
+        match self {
+            Material::Lambertian(l) => l.pdf(rec, wi, wo),
+            Material::Metal(m) => m.pdf(rec, wi, wo),
+            Material::Conductor(c) => c.pdf(rec, wi, wo),
+            Material::RoughDielectric(d) => d.pdf(rec, wi, wo),
+            Material::Principled(p) => p.pdf(rec, wi, wo),
+            Material::Coated(c) => c.pdf(rec, wi, wo),
+            Material::Cutout(c) => c.pdf(rec, wi, wo),
+            Material::Hair(h) => h.pdf(rec, wi, wo),
+            Material::Custom(c) => c.pdf(rec, wi, wo),
+            _ => 0.0,
+        }
+    }
+    pub fn emitted(&self, rec: &HitRecord) -> Color {
+        match self {
+            Material::DiffuseLight(d) => d.emitted(rec),
+            Material::Coated(c) => c.emitted(rec),
+            Material::Cutout(c) => c.emitted(rec),
+            Material::Custom(c) => c.emitted(rec),
+            _ => Color::zeros(),
+        }
+    }
+    // Whether a surface made of this material is there where `r` would hit
+    // it at `t`, for hit routines to skip the holes of cutouts
+    pub fn is_opaque_at(&self, r: &Ray, t: f64, u: f64, v: f64, p: Point3) -> bool {
+        let opacity = match self {
+            Material::Cutout(c) => c.opacity(u, v, p),
+            _ => return true,
+        };
+        opacity >= 1.0 || (opacity > 0.0 && cutout::hashed_uniform(r, t) < opacity)
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -35,16 +175,34 @@ pub struct Lambertian {
 }
 
 impl Lambertian {
+    // Constructors of materials hand back the `Material` wrapping them
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(albedo: Color) -> Material {
         Material::Lambertian(Lambertian { albedo })
     }
-    fn scatter(&self, r_in: &Ray, rec: &mut HitRecord, rng: &mut ThreadRng) -> Option<(Vec3, Ray)> {
-        let mut direction = rec.normal + Vec3::rand_unit(rng);
-
-        if direction.near_zero() {
-            direction = rec.normal;
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &mut HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<ScatterRecord> {
+        let direction = Onb::from_w(rec.normal).local(Vec3::rand_cosine_direction(rng));
+        let pdf = self.pdf(rec, direction, -r_in.direction);
+        if pdf <= 0.0 {
+            return Option::None;
         }
-        Option::Some((self.albedo, Ray::new(rec.p, direction, r_in.time)))
+        Option::Some(ScatterRecord::new(
+            self.albedo,
+            Ray::new(rec.p, direction, r_in.time),
+            pdf,
+        ))
+    }
+    fn eval(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> Color {
+        self.albedo * self.pdf(rec, wi, wo)
+    }
+    // Cosine distribution
+    fn pdf(&self, rec: &HitRecord, wi: Vec3, _wo: Vec3) -> f64 {
+        rec.normal.dot(wi.normalize()).max(0.0) / PI
     }
 }
 
@@ -55,40 +213,102 @@ pub struct Metal {
 }
 
 impl Metal {
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(albedo: Color, fuzz: f64) -> Material {
         Material::Metal(Metal { albedo, fuzz })
     }
-    fn scatter(&self, r_in: &Ray, rec: &mut HitRecord, rng: &mut ThreadRng) -> Option<(Vec3, Ray)> {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &mut HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<ScatterRecord> {
+        // Two-sided like every material: `rec.normal` faces the incoming
+        // ray whichever side was hit, see `Sided` for one-sided surfaces
         let reflected = r_in.direction.normalize().reflect(rec.normal);
-        if r_in.direction.dot(rec.normal) > 0. {
-            return Option::None;
+        if self.fuzz <= 0.0 {
+            return Option::Some(ScatterRecord::delta(
+                self.albedo,
+                Ray::new(rec.p, reflected, r_in.time),
+            ));
         }
-        Option::Some((
+        let direction = reflected + self.fuzz * Vec3::rand_in_sphere(rng);
+        let pdf = self.pdf(rec, direction, -r_in.direction);
+        Option::Some(ScatterRecord::new(
             self.albedo,
-            Ray::new(
-                rec.p,
-                reflected + self.fuzz * Vec3::rand_in_sphere(rng),
-                r_in.time,
-            ),
+            Ray::new(rec.p, direction, r_in.time),
+            pdf,
         ))
     }
+    fn eval(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> Color {
+        self.albedo * self.pdf(rec, wi, wo)
+    }
+    // Density of the normalized `reflected + fuzz * rand_in_sphere`: the
+    // volume of the fuzz ball swept by the direction, weighted by s^2 ds.
+    fn pdf(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> f64 {
+        if self.fuzz <= 0.0 {
+            return 0.0;
+        }
+        let reflected = (-wo).normalize().reflect(rec.normal);
+        let cosine = wi.normalize().dot(reflected);
+        let discriminant = cosine * cosine - (1.0 - self.fuzz * self.fuzz);
+        if discriminant < 0.0 {
+            return 0.0;
+        }
+        let s_max = cosine + discriminant.sqrt();
+        let s_min = (cosine - discriminant.sqrt()).max(0.0);
+        if s_max <= 0.0 {
+            return 0.0;
+        }
+        (s_max.powi(3) - s_min.powi(3)) / (4.0 * PI * self.fuzz.powi(3))
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
 pub struct Dielectric {
-    ir: f64,
+    ir: Ior,
+    // Absorption coefficient of the medium, per unit of distance
+    absorption: Color,
 }
 
 impl Dielectric {
-    pub fn new(ir: f64) -> Material {
-        Material::Dielectric(Dielectric { ir })
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(ir: impl Into<Ior>) -> Material {
+        Self::tinted(ir, Color::ones(), 0.0)
     }
-    fn scatter(&self, r_in: &Ray, rec: &mut HitRecord, rng: &mut ThreadRng) -> Option<(Vec3, Ray)> {
-        let refraction_ratio = if rec.front_face {
-            1.0 / self.ir
-        } else {
-            self.ir
-        };
+    // Glass letting through `color` of the light crossing 1/density units of it
+    pub fn tinted(ir: impl Into<Ior>, color: Color, density: f64) -> Material {
+        let ln = |channel: f64| channel.max(1e-6).ln();
+        Material::Dielectric(Dielectric {
+            ir: ir.into(),
+            // Channels let through at 0 are clamped to absorb a lot rather
+            // than infinitely, which would be NaN without density
+            absorption: -density * Color::new(ln(color.x), ln(color.y), ln(color.z)),
+        })
+    }
+    // Beer-Lambert attenuation of the segment `r_in` travelled to reach `rec`,
+    // which is inside the medium when a back face is hit
+    fn transmittance(&self, r_in: &Ray, rec: &HitRecord) -> Color {
+        if rec.front_face {
+            return Color::ones();
+        }
+        let distance = rec.t * r_in.direction.length();
+        let optical_depth = distance * self.absorption;
+        Color::new(
+            (-optical_depth.x).exp(),
+            (-optical_depth.y).exp(),
+            (-optical_depth.z).exp(),
+        )
+    }
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &mut HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<ScatterRecord> {
+        let ir = self.ir.at(r_in.wavelength);
+        let refraction_ratio = if rec.front_face { 1.0 / ir } else { ir };
+        let attenuation = self.transmittance(r_in, rec);
         let direction_norm = r_in.direction.normalize();
         let cos_theta = (-direction_norm).dot(rec.normal).min(1.0);
         let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
@@ -99,10 +319,16 @@ impl Dielectric {
             || Self::reflectance(cos_theta, refraction_ratio) > rng.gen_range(0.0..1.0)
         {
             let reflect = direction_norm.reflect(rec.normal);
-            Option::Some((Color::ones(), Ray::new(rec.p, reflect, r_in.time)))
+            Option::Some(ScatterRecord::delta(
+                attenuation,
+                Ray::new(rec.p, reflect, r_in.time),
+            ))
         } else {
             let refracted = direction_norm.refract(rec.normal, refraction_ratio);
-            Option::Some((Color::ones(), Ray::new(rec.p, refracted, r_in.time)))
+            Option::Some(ScatterRecord::delta(
+                attenuation,
+                Ray::new(rec.p, refracted, r_in.time),
+            ))
         }
     }
     fn reflectance(cosine: f64, refraction_ratio: f64) -> f64 {
@@ -112,3 +338,345 @@ impl Dielectric {
         r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
     }
 }
+
+#[derive(Debug, Copy, Clone)]
+pub struct DiffuseLight {
+    emit: Color,
+}
+
+impl DiffuseLight {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(emit: Color) -> Material {
+        Material::DiffuseLight(DiffuseLight { emit })
+    }
+    fn emitted(&self, _rec: &HitRecord) -> Color {
+        self.emit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Coated, Conductor, Dielectric, Hair, Ior, Lambertian, Material, Metal, Principled,
+        RoughDielectric, Scatter, ScatterRecord,
+    };
+    use crate::{
+        raytracer::{hittable::texture::Texture, HitRecord, Ray},
+        vec3::{Color, Onb, Point3, Vec3},
+    };
+    use rand::{thread_rng, Rng, RngCore};
+    use std::f64::consts::PI;
+
+    // Lambertian again, from outside of the enum
+    #[derive(Debug)]
+    struct Matte(Color);
+
+    impl Scatter for Matte {
+        fn scatter(
+            &self,
+            r_in: &Ray,
+            rec: &mut HitRecord,
+            rng: &mut dyn RngCore,
+        ) -> Option<ScatterRecord> {
+            let direction = Onb::from_w(rec.normal).local(Vec3::rand_cosine_direction(rng));
+            let pdf = self.pdf(rec, direction, -r_in.direction);
+            Some(ScatterRecord::new(
+                self.0,
+                Ray::new(rec.p, direction, r_in.time),
+                pdf,
+            ))
+        }
+        fn eval(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> Color {
+            self.0 * self.pdf(rec, wi, wo)
+        }
+        fn pdf(&self, rec: &HitRecord, wi: Vec3, _wo: Vec3) -> f64 {
+            rec.normal.dot(wi.normalize()).max(0.0) / PI
+        }
+    }
+
+    fn hit_record(front_face: bool) -> HitRecord<'static> {
+        let mut rec = HitRecord::new();
+        rec.p = Point3::zeros();
+        rec.normal = Vec3::up();
+        rec.front_face = front_face;
+        rec
+    }
+
+    // Probability for `scatter` to land in the cone of half-angle acos(cos_max)
+    // around `axis`, estimated by sampling then by integrating `pdf`
+    fn cone_probabilities(
+        material: &Material,
+        front_face: bool,
+        r_in: &Ray,
+        axis: Vec3,
+        cos_max: f64,
+        n: usize,
+    ) -> (f64, f64, f64) {
+        let mut rng = thread_rng();
+        let mut rec = hit_record(front_face);
+        let wo = -r_in.direction;
+
+        let mut hits = 0;
+        for _ in 0..n {
+            // Absorbed samples never land anywhere
+            let srec = match material.scatter(r_in, &mut rec, &mut rng) {
+                Some(srec) => srec,
+                None => continue,
+            };
+            // Only coats mix a delta lobe with the ones `pdf` describes
+            if srec.is_delta && matches!(material, Material::Coated(_)) {
+                continue;
+            }
+            assert!(!srec.is_delta);
+            let wi = srec.ray.direction;
+            let expected = material.eval(&rec, wi, wo) / material.pdf(&rec, wi, wo);
+            assert!((srec.attenuation - expected).length() < 1e-6 * expected.length().max(1.0));
+            if wi.normalize().dot(axis) > cos_max {
+                hits += 1;
+            }
+        }
+        let sampled = hits as f64 / n as f64;
+
+        let onb = Onb::from_w(axis);
+        let solid_angle = 2.0 * PI * (1.0 - cos_max);
+        let mut integral = 0.0;
+        let mut integral_squared = 0.0;
+        for _ in 0..n {
+            let z = rng.gen_range(cos_max..1.0);
+            let phi = rng.gen_range(0.0..2.0 * PI);
+            let r = (1.0 - z * z).sqrt();
+            let wi = onb.local(Vec3::new(r * phi.cos(), r * phi.sin(), z));
+            let value = solid_angle * material.pdf(&rec, wi, wo);
+            integral += value;
+            integral_squared += value * value;
+        }
+        let integrated = integral / n as f64;
+        let variance = integral_squared / n as f64 - integrated * integrated;
+
+        let std_err = ((sampled * (1.0 - sampled) + variance) / n as f64).sqrt();
+        (sampled, integrated, std_err)
+    }
+
+    fn check_sampling_matches_pdf(material: Material, front_face: bool) {
+        let r_in = Ray::new(Point3::new(-1., 1., 0.), Vec3::new(1., -0.7, 0.2), 0.0);
+        let reflected = r_in.direction.normalize().reflect(Vec3::up());
+        for axis in [
+            Vec3::up(),
+            reflected,
+            Vec3::new(0.5, 0.5, -0.5).normalize(),
+            Vec3::new(-0.2, -1.0, 0.1).normalize(),
+        ] {
+            for cos_max in [0.95, 0.7] {
+                let (sampled, integrated, std_err) =
+                    cone_probabilities(&material, front_face, &r_in, axis, cos_max, 50_000);
+                assert!(
+                    (sampled - integrated).abs() <= 5.0 * std_err + 1e-3,
+                    "{:?} around {:?}: sampled {} vs pdf {}",
+                    material,
+                    axis,
+                    sampled,
+                    integrated
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn lambertian_sampling_matches_pdf() {
+        check_sampling_matches_pdf(Lambertian::new(Color::new(0.5, 0.2, 0.9)), true);
+    }
+
+    #[test]
+    fn metal_sampling_matches_pdf() {
+        check_sampling_matches_pdf(Metal::new(Color::new(0.9, 0.8, 0.7), 0.3), true);
+        check_sampling_matches_pdf(Metal::new(Color::new(0.9, 0.8, 0.7), 1.2), true);
+    }
+
+    #[test]
+    fn conductor_sampling_matches_pdf() {
+        check_sampling_matches_pdf(Conductor::gold(0.4), true);
+        check_sampling_matches_pdf(Conductor::aluminium(0.8), true);
+    }
+
+    #[test]
+    fn rough_dielectric_sampling_matches_pdf() {
+        check_sampling_matches_pdf(RoughDielectric::new(1.5, 0.5), true);
+        check_sampling_matches_pdf(RoughDielectric::new(1.5, 0.5), false);
+    }
+
+    #[test]
+    fn principled_sampling_matches_pdf() {
+        check_sampling_matches_pdf(Principled::builder(Color::new(0.8, 0.3, 0.2)).build(), true);
+        check_sampling_matches_pdf(
+            Principled::builder(Color::new(0.9, 0.7, 0.3))
+                .metallic(0.6)
+                .roughness(0.3)
+                .clearcoat(1.0, 0.2)
+                .sheen(0.5)
+                .build(),
+            true,
+        );
+        let glass = Principled::builder(Color::new(0.9, 0.95, 1.0))
+            .roughness(0.4)
+            .transmission(0.8, 1.5);
+        check_sampling_matches_pdf(glass.build(), true);
+        check_sampling_matches_pdf(glass.build(), false);
+    }
+
+    #[test]
+    fn hair_sampling_matches_pdf() {
+        let hair = Hair::new(Color::new(0.4, 0.25, 0.1), Color::new(0.3, 0.3, 0.3), 40.0);
+        check_sampling_matches_pdf(hair, true);
+    }
+
+    #[test]
+    fn coated_sampling_matches_pdf() {
+        let varnish = Coated::new(Conductor::copper(0.4), 1.5);
+        check_sampling_matches_pdf(varnish.clone(), true);
+        check_sampling_matches_pdf(varnish, false);
+        let film = Coated::thin_film(Lambertian::new(Color::new(0.6, 0.1, 0.1)), 1.4, 400.0);
+        check_sampling_matches_pdf(film, true);
+    }
+
+    #[test]
+    fn principled_textures() {
+        // Metallic only on the even cells of a checker
+        let material: Material = Principled::builder(Color::new(1.0, 0.0, 0.0))
+            .metallic(Texture::checker(Color::ones(), Color::zeros(), 1.0))
+            .roughness(0.2)
+            .build();
+        let wi = Vec3::new(1., 1., 0.);
+        let wo = Vec3::new(-1., 1., 0.);
+        let mut rec = hit_record(true);
+
+        rec.p = Point3::new(0.5, 0.0, 0.5);
+        let metal = material.eval(&rec, wi, wo);
+        rec.p = Point3::new(1.5, 0.0, 0.5);
+        let plastic = material.eval(&rec, wi, wo);
+        // A red metal reflects red, red plastic has a white highlight
+        assert!(metal.x > plastic.x);
+        assert!(metal.y < 0.1 * plastic.y);
+    }
+
+    #[test]
+    fn microfacet_energy_conservation() {
+        // Average sample weight is the directional albedo, at most one
+        let mut rng = thread_rng();
+        let r_in = Ray::new(Point3::new(-1., 1., 0.), Vec3::new(1., -1., 0.), 0.0);
+        for (material, front_face) in [
+            (
+                Conductor::new(Color::ones() * 0.01, Color::ones() * 100., 0.5),
+                true,
+            ),
+            (Conductor::copper(0.3), true),
+            (RoughDielectric::new(1.5, 0.5), true),
+            (RoughDielectric::new(1.5, 0.5), false),
+        ] {
+            let mut rec = hit_record(front_face);
+            let n = 50_000;
+            let mut albedo = Color::zeros();
+            for _ in 0..n {
+                if let Some(srec) = material.scatter(&r_in, &mut rec, &mut rng) {
+                    albedo += srec.attenuation;
+                }
+            }
+            let albedo = albedo / n as f64;
+            assert!(albedo.x <= 1.01 && albedo.y <= 1.01 && albedo.z <= 1.01);
+            // Single scattering misses some energy on rough surfaces, not most
+            assert!(albedo.x > 0.8, "{:?}: {:?}", material, albedo);
+        }
+    }
+
+    #[test]
+    fn dielectric_absorption() {
+        let mut rng = thread_rng();
+        let material = Dielectric::tinted(1.5, Color::new(0.5, 1.0, 0.25), 0.5);
+        let r_in = Ray::new(Point3::new(0., -4., 0.), Vec3::new(0., 2., 0.), 0.0);
+
+        // Leaving the glass after 4 units, i.e. twice the reference distance
+        let mut rec = hit_record(false);
+        rec.normal = -Vec3::up();
+        rec.t = 2.0;
+        let srec = material.scatter(&r_in, &mut rec, &mut rng).unwrap();
+        let expected = Color::new(0.25, 1.0, 0.0625);
+        assert!((srec.attenuation - expected).length() < 1e-12);
+
+        // Nothing is absorbed on the way in
+        let mut rec = hit_record(true);
+        rec.normal = -Vec3::up();
+        rec.t = 2.0;
+        let srec = material.scatter(&r_in, &mut rec, &mut rng).unwrap();
+        assert_eq!(srec.attenuation, Color::ones());
+
+        // Without density nothing is absorbed, even channels let through at 0
+        let clear = Dielectric::tinted(1.5, Color::new(1., 0., 0.), 0.0);
+        let mut rec = hit_record(false);
+        rec.normal = -Vec3::up();
+        rec.t = 2.0;
+        let srec = clear.scatter(&r_in, &mut rec, &mut rng).unwrap();
+        assert_eq!(srec.attenuation, Color::ones());
+    }
+
+    #[test]
+    fn metal_back_faces() {
+        // Hit from behind, metal still mirrors about the normal facing the ray
+        let mut rng = thread_rng();
+        let mut rec = hit_record(false);
+        let r_in = Ray::new(Point3::new(-1., 1., 0.), Vec3::new(1., -1., 0.), 0.0);
+        let srec = Metal::new(Color::ones(), 0.0)
+            .scatter(&r_in, &mut rec, &mut rng)
+            .unwrap();
+        assert!((srec.ray.direction - Vec3::new(1., 1., 0.).normalize()).length() < 1e-12);
+    }
+
+    #[test]
+    fn delta_lobes() {
+        let mut rng = thread_rng();
+        let mut rec = hit_record(true);
+        let r_in = Ray::new(Point3::new(-1., 1., 0.), Vec3::new(1., -1., 0.), 0.0);
+        for material in [Metal::new(Color::ones(), 0.0), Dielectric::new(1.5)] {
+            let srec = material.scatter(&r_in, &mut rec, &mut rng).unwrap();
+            assert!(srec.is_delta);
+            let wi = srec.ray.direction;
+            assert_eq!(material.pdf(&rec, wi, -r_in.direction), 0.0);
+            assert_eq!(material.eval(&rec, wi, -r_in.direction), Color::zeros());
+        }
+    }
+
+    #[test]
+    fn rough_dispersion() {
+        let mut rec = hit_record(true);
+        let wo = Vec3::new(-1., 1., 0.).normalize();
+        let ior = Ior::dense_flint();
+        // Where the smooth glass sends blue light
+        let wi = (-wo).refract(Vec3::up(), 1.0 / ior.at(Some(450.)));
+        for material in [
+            RoughDielectric::new(ior, 0.1),
+            Principled::builder(Color::ones())
+                .roughness(0.1)
+                .transmission(1.0, ior)
+                .build(),
+        ] {
+            rec.wavelength = Some(450.);
+            let blue = material.eval(&rec, wi, wo).length();
+            rec.wavelength = Some(650.);
+            let red = material.eval(&rec, wi, wo).length();
+            assert!(blue > red, "{:?}: {} vs {}", material, blue, red);
+        }
+    }
+
+    #[test]
+    fn custom_materials() {
+        let albedo = Color::new(0.3, 0.6, 0.9);
+        check_sampling_matches_pdf(Material::custom(Matte(albedo)), true);
+
+        let rec = hit_record(true);
+        let wi = Vec3::new(0.3, 1., 0.2);
+        let wo = Vec3::new(-0.5, 1., 0.);
+        let matte = Material::custom(Matte(albedo));
+        let lambertian = Lambertian::new(albedo);
+        assert_eq!(matte.eval(&rec, wi, wo), lambertian.eval(&rec, wi, wo));
+        assert_eq!(matte.pdf(&rec, wi, wo), lambertian.pdf(&rec, wi, wo));
+        assert_eq!(matte.emitted(&rec), Color::zeros());
+    }
+}
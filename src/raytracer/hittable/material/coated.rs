@@ -0,0 +1,145 @@
+use super::{Dielectric, Material, ScatterRecord};
+use crate::{
+    raytracer::{spectrum, HitRecord, Ray},
+    vec3::{Color, Vec3},
+};
+use rand::{Rng, RngCore};
+use std::{f64::consts::PI, sync::Arc};
+
+// Smooth dielectric layer on top of a base material. Light is either
+// mirrored by the coat or goes through it, unbent, to the base below.
+#[derive(Debug, Clone)]
+pub struct Coated {
+    base: Arc<Material>,
+    ir: f64,
+    // Thickness of the coat in nanometers for thin-film interference, zero
+    // for a thick coat
+    thickness: f64,
+}
+
+impl Coated {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(base: Material, ir: f64) -> Material {
+        Self::thin_film(base, ir, 0.0)
+    }
+    pub fn thin_film(base: Material, ir: f64, thickness: f64) -> Material {
+        Material::Coated(Coated {
+            base: Arc::new(base),
+            ir,
+            thickness,
+        })
+    }
+
+    // Reflectance of a film surrounded by air, from the interference of the
+    // waves reflected by both of its sides (Airy summation), averaged over
+    // both polarizations
+    fn film_reflectance(&self, cos_i: f64, wavelength: f64) -> f64 {
+        let n = self.ir;
+        let sin_t2 = (1.0 - cos_i * cos_i) / (n * n);
+        let cos_t = (1.0 - sin_t2).max(0.0).sqrt();
+        let r_s = (cos_i - n * cos_t) / (cos_i + n * cos_t);
+        let r_p = (n * cos_i - cos_t) / (n * cos_i + cos_t);
+
+        let cos_delta = (4.0 * PI * n * self.thickness * cos_t / wavelength).cos();
+        let airy = |r: f64| {
+            let r2 = r * r;
+            r2 * (2.0 - 2.0 * cos_delta) / (1.0 - 2.0 * r2 * cos_delta + r2 * r2)
+        };
+        0.5 * (airy(r_s) + airy(r_p))
+    }
+    fn reflectance(&self, cosine: f64, wavelength: Option<f64>) -> Color {
+        if self.thickness <= 0.0 {
+            return Dielectric::reflectance(cosine, self.ir) * Color::ones();
+        }
+        match wavelength {
+            Some(lambda) => self.film_reflectance(cosine, lambda) * Color::ones(),
+            None => spectrum::to_rgb(|lambda| self.film_reflectance(cosine, lambda)),
+        }
+    }
+    // Chance to sample the mirror reflection of the coat
+    fn reflect_probability(reflectance: Color) -> f64 {
+        reflectance.luminance().clamp(0.0, 1.0)
+    }
+    // Light reaching the base crosses the coat twice when it is reflected
+    // back up, but only once when the base lets it through
+    fn transmittance(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> Color {
+        let cos_o = wo.normalize().dot(rec.normal).abs();
+        let cos_i = wi.normalize().dot(rec.normal);
+        let mut transmittance = Color::ones() - self.reflectance(cos_o, rec.wavelength);
+        if cos_i > 0.0 {
+            transmittance *= Color::ones() - self.reflectance(cos_i, rec.wavelength);
+        }
+        transmittance
+    }
+
+    pub(super) fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &mut HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<ScatterRecord> {
+        let direction_norm = r_in.direction.normalize();
+        let cos_o = (-direction_norm).dot(rec.normal).abs();
+        let reflectance = self.reflectance(cos_o, r_in.wavelength);
+        let p_reflect = Self::reflect_probability(reflectance);
+
+        if rng.gen_range(0.0..1.0) < p_reflect {
+            return Option::Some(ScatterRecord::delta(
+                reflectance / p_reflect,
+                Ray::new(rec.p, direction_norm.reflect(rec.normal), r_in.time),
+            ));
+        }
+        let mut srec = self.base.scatter(r_in, rec, rng)?;
+        srec.attenuation *=
+            self.transmittance(rec, srec.ray.direction, -r_in.direction) / (1.0 - p_reflect);
+        srec.pdf *= 1.0 - p_reflect;
+        Option::Some(srec)
+    }
+    pub(super) fn eval(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> Color {
+        self.base.eval(rec, wi, wo) * self.transmittance(rec, wi, wo)
+    }
+    pub(super) fn pdf(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> f64 {
+        let cos_o = wo.normalize().dot(rec.normal).abs();
+        let p_reflect = Self::reflect_probability(self.reflectance(cos_o, rec.wavelength));
+        (1.0 - p_reflect) * self.base.pdf(rec, wi, wo)
+    }
+    pub(super) fn emitted(&self, rec: &HitRecord) -> Color {
+        self.base.emitted(rec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Coated;
+    use crate::raytracer::hittable::material::{Dielectric, Material};
+
+    fn coat(material: &Material) -> &Coated {
+        match material {
+            Material::Coated(coated) => coated,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn thin_film() {
+        let bubble = Coated::thin_film(Dielectric::new(1.0), 1.33, 300.0);
+        let film = coat(&bubble);
+
+        // A quarter-wave film reflects the most, a half-wave one not at all
+        let quarter = 4.0 * 1.33 * 300.0;
+        let half = 2.0 * 1.33 * 300.0;
+        assert!(film.film_reflectance(1.0, half) < 1e-12);
+        let r = (0.33f64 / 2.33).powi(2);
+        let expected = 4.0 * r / (1.0 + r).powi(2);
+        assert!((film.film_reflectance(1.0, quarter) - expected).abs() < 1e-12);
+
+        // Interference colors the reflection in RGB mode
+        let color = film.reflectance(1.0, None);
+        assert!((color.x - color.z).abs() > 0.01, "{:?}", color);
+
+        // Thick coats fall back to Schlick's approximation
+        let varnish = Coated::new(Dielectric::new(1.0), 1.5);
+        let reflectance = coat(&varnish).reflectance(1.0, Some(500.0));
+        assert!((reflectance.y - 0.04).abs() < 1e-12);
+    }
+}
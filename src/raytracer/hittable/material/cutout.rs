@@ -0,0 +1,142 @@
+use super::{Material, ScatterRecord};
+use crate::{
+    raytracer::{hittable::texture::Texture, HitRecord, Ray},
+    vec3::{Color, Point3, Vec3},
+};
+use rand::RngCore;
+use std::sync::Arc;
+
+// Base material with holes: surfaces are only hit where the opacity texture
+// says so, fractional opacities letting rays through at random
+#[derive(Debug, Clone)]
+pub struct Cutout {
+    base: Arc<Material>,
+    opacity: Texture,
+}
+
+impl Cutout {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(base: Material, opacity: impl Into<Texture>) -> Material {
+        Material::Cutout(Cutout {
+            base: Arc::new(base),
+            opacity: opacity.into(),
+        })
+    }
+    pub(super) fn opacity(&self, u: f64, v: f64, p: Point3) -> f64 {
+        self.opacity.scalar(u, v, p).clamp(0.0, 1.0)
+    }
+
+    pub(super) fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &mut HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<ScatterRecord> {
+        self.base.scatter(r_in, rec, rng)
+    }
+    pub(super) fn eval(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> Color {
+        self.base.eval(rec, wi, wo)
+    }
+    pub(super) fn pdf(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> f64 {
+        self.base.pdf(rec, wi, wo)
+    }
+    pub(super) fn emitted(&self, rec: &HitRecord) -> Color {
+        self.base.emitted(rec)
+    }
+}
+
+// Uniform number in [0, 1) derived from a ray and a distance along it. Hit
+// routines have no random generator, and hashing keeps repeated queries of
+// the same ray, like light pdfs, consistent with the first one.
+pub(super) fn hashed_uniform(r: &Ray, t: f64) -> f64 {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for value in [
+        r.origin.x,
+        r.origin.y,
+        r.origin.z,
+        r.direction.x,
+        r.direction.y,
+        r.direction.z,
+        t,
+    ] {
+        h ^= value.to_bits();
+        // splitmix64 finalizer
+        h = h.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        h = (h ^ (h >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        h = (h ^ (h >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        h ^= h >> 31;
+    }
+    (h >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hashed_uniform, Cutout};
+    use crate::{
+        raytracer::{
+            hittable::{material::Lambertian, texture::Texture},
+            AaRect, HitRecord, Hittable, Ray, Sphere,
+        },
+        vec3::{Color, Point3, Vec3},
+    };
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn hashed_uniform_is_uniform() {
+        let mut rng = thread_rng();
+        let n = 100_000;
+        let mut below = 0;
+        for _ in 0..n {
+            let r = Ray::new(
+                Point3::new(rng.gen_range(-1.0..1.0), 0., 0.),
+                Vec3::new(0., rng.gen_range(-1.0..1.0), 1.),
+                0.0,
+            );
+            let x = hashed_uniform(&r, 2.0);
+            assert!((0.0..1.0).contains(&x));
+            if x < 0.3 {
+                below += 1;
+            }
+        }
+        assert!((below as f64 / n as f64 - 0.3).abs() < 0.01);
+    }
+
+    #[test]
+    fn holes() {
+        let white = Lambertian::new(Color::ones());
+        let fence = Cutout::new(
+            white.clone(),
+            Texture::checker(Color::ones(), Color::zeros(), 1.0),
+        );
+        let rect = AaRect::xy(-5., 5., -5., 5., 0., fence);
+        let mut rec = HitRecord::new();
+        let ray = |x: f64| Ray::new(Point3::new(x, 0.5, 2.), Vec3::new(0., 0., -1.), 0.0);
+        assert!(rect.hit(&ray(0.5), 0.001, f64::INFINITY, &mut rec));
+        assert!(!rect.hit(&ray(1.5), 0.001, f64::INFINITY, &mut rec));
+
+        // Holes in the front of a sphere show its back
+        let shell = Cutout::new(white, Texture::checker(Color::ones(), Color::zeros(), 1.0));
+        let sphere = Sphere::new(Point3::new(0., 0., -1.), 1., shell);
+        let ray = Ray::new(Point3::new(0.1, 0.1, 5.), Vec3::new(0., 0., -1.), 0.0);
+        assert!(sphere.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert!(rec.p.z < -1.0 && !rec.front_face);
+    }
+
+    #[test]
+    fn fractional_opacity() {
+        let mut rng = thread_rng();
+        let veil = Cutout::new(Lambertian::new(Color::ones()), 0.25);
+        let rect = AaRect::xy(-1., 1., -1., 1., 0., veil);
+        let mut rec = HitRecord::new();
+        let n = 20_000;
+        let mut hits = 0;
+        for _ in 0..n {
+            let origin = Point3::new(rng.gen_range(-0.5..0.5), rng.gen_range(-0.5..0.5), 1.);
+            let ray = Ray::new(origin, Vec3::new(0., 0., -1.), 0.0);
+            if rect.hit(&ray, 0.001, f64::INFINITY, &mut rec) {
+                hits += 1;
+            }
+        }
+        assert!((hits as f64 / n as f64 - 0.25).abs() < 0.02);
+    }
+}
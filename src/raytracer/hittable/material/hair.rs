@@ -0,0 +1,142 @@
+use super::{Material, ScatterRecord};
+use crate::{
+    raytracer::{HitRecord, Ray},
+    vec3::{Color, Onb, Vec3},
+};
+use rand::{Rng, RngCore};
+use std::f64::consts::{FRAC_PI_2, PI};
+
+// Kajiya-Kay fiber shading, the fiber running along `dpdu`: a diffuse term
+// following the sine to the fiber, scattering light all around it, and a
+// specular cone of reflections around it whose sharpness is `exponent`
+#[derive(Debug, Copy, Clone)]
+pub struct Hair {
+    diffuse: Color,
+    specular: Color,
+    exponent: f64,
+}
+
+impl Hair {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(diffuse: Color, specular: Color, exponent: f64) -> Material {
+        Material::Hair(Hair {
+            diffuse,
+            specular,
+            exponent: exponent.max(1.0),
+        })
+    }
+    fn tangent(rec: &HitRecord) -> Vec3 {
+        if rec.dpdu.near_zero() {
+            Onb::from_w(rec.normal).u
+        } else {
+            rec.dpdu.normalize()
+        }
+    }
+    fn specular_probability(&self) -> f64 {
+        let (diffuse, specular) = (self.diffuse.luminance(), self.specular.luminance());
+        if diffuse + specular > 0.0 {
+            specular / (diffuse + specular)
+        } else {
+            0.0
+        }
+    }
+    // Scale of the logistic distribution of the angle to the cone, whose
+    // standard deviation matches that of cos^exponent
+    fn spread(&self) -> f64 {
+        3f64.sqrt() / (PI * self.exponent.sqrt())
+    }
+
+    pub(super) fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &mut HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<ScatterRecord> {
+        let wo = -r_in.direction;
+        let direction = if rng.gen_range(0.0..1.0) < self.specular_probability() {
+            // Angles are measured from the plane normal to the fiber, the
+            // cone mirroring the outgoing one
+            let tangent = Self::tangent(rec);
+            let theta_o = tangent.dot(wo.normalize()).clamp(-1.0, 1.0).asin();
+            let x: f64 = rng.gen_range(1e-9..1.0 - 1e-9);
+            let theta = (x / (1.0 - x)).ln() * self.spread() - theta_o;
+            if theta.abs() > FRAC_PI_2 {
+                return None;
+            }
+            let phi = rng.gen_range(0.0..2.0 * PI);
+            let frame = Onb::from_w(tangent);
+            frame.local(Vec3::new(
+                theta.cos() * phi.cos(),
+                theta.cos() * phi.sin(),
+                theta.sin(),
+            ))
+        } else {
+            Vec3::rand_unit(rng)
+        };
+        let pdf = self.pdf(rec, direction, wo);
+        if pdf <= 0.0 {
+            return None;
+        }
+        Some(ScatterRecord::new(
+            self.eval(rec, direction, wo) / pdf,
+            Ray::new(rec.p, direction, r_in.time),
+            pdf,
+        ))
+    }
+    pub(super) fn eval(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> Color {
+        let tangent = Self::tangent(rec);
+        let sin_i = tangent.dot(wi.normalize()).clamp(-1.0, 1.0);
+        let sin_o = tangent.dot(wo.normalize()).clamp(-1.0, 1.0);
+        let (cos_i, cos_o) = ((1.0 - sin_i * sin_i).sqrt(), (1.0 - sin_o * sin_o).sqrt());
+        // The sine to the fiber integrates to π² over the sphere
+        let diffuse = self.diffuse * cos_i / (PI * PI);
+        // Cosine of the angle to the cone, the lobe roughly normalized
+        let cone = (cos_i * cos_o - sin_i * sin_o).max(0.0);
+        let normalization = (self.exponent / (2.0 * PI)).sqrt() / (2.0 * PI);
+        diffuse + self.specular * normalization * cone.powf(self.exponent)
+    }
+    pub(super) fn pdf(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> f64 {
+        let tangent = Self::tangent(rec);
+        let sin_i = tangent.dot(wi.normalize()).clamp(-1.0, 1.0);
+        let sin_o = tangent.dot(wo.normalize()).clamp(-1.0, 1.0);
+        let delta = sin_i.asin() + sin_o.asin();
+        let s = self.spread();
+        let logistic = (-delta.abs() / s).exp() / (s * (1.0 + (-delta.abs() / s).exp()).powi(2));
+        // Uniform in azimuth around the fiber, dω being cos θ dθ dφ
+        let cos_i = (1.0 - sin_i * sin_i).sqrt().max(1e-9);
+        let specular = logistic / (2.0 * PI * cos_i);
+
+        let p = self.specular_probability();
+        p * specular + (1.0 - p) / (4.0 * PI)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Hair;
+    use crate::{
+        raytracer::HitRecord,
+        vec3::{Color, Point3, Vec3},
+    };
+    use rand::thread_rng;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn diffuse_albedo() {
+        // White fibers without highlights scatter all the light around them
+        let hair = Hair::new(Color::ones(), Color::zeros(), 10.0);
+        let mut rec = HitRecord::new();
+        rec.p = Point3::zeros();
+        rec.normal = Vec3::up();
+        rec.dpdu = Vec3::new(1., 1., 0.);
+        let wo = Vec3::new(0.3, 1., -0.2);
+        let mut rng = thread_rng();
+        let n = 100_000;
+        let mut albedo = 0.0;
+        for _ in 0..n {
+            let wi = Vec3::rand_unit(&mut rng);
+            albedo += 4.0 * PI * hair.eval(&rec, wi, wo).x / n as f64;
+        }
+        assert!((albedo - 1.0).abs() < 0.01, "{}", albedo);
+    }
+}
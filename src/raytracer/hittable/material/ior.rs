@@ -0,0 +1,75 @@
+// Wavelength used for the index of refraction of RGB rays (sodium d-line)
+const LAMBDA_D: f64 = 587.6;
+
+// Index of refraction, optionally varying with the wavelength
+#[derive(Debug, Copy, Clone)]
+pub enum Ior {
+    Constant(f64),
+    // n = a + b / lambda², lambda in micrometers
+    Cauchy { a: f64, b: f64 },
+    // n² = 1 + sum(b_i lambda² / (lambda² - c_i)), lambda in micrometers
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl Ior {
+    pub fn cauchy(a: f64, b: f64) -> Self {
+        Ior::Cauchy { a, b }
+    }
+    pub fn sellmeier(b: [f64; 3], c: [f64; 3]) -> Self {
+        Ior::Sellmeier { b, c }
+    }
+    // Schott N-BK7 crown glass
+    pub fn bk7() -> Self {
+        Self::sellmeier(
+            [1.03961212, 0.231792344, 1.01046945],
+            [0.00600069867, 0.0200179144, 103.560653],
+        )
+    }
+    // Schott SF11 dense flint glass, strongly dispersive
+    pub fn dense_flint() -> Self {
+        Self::sellmeier(
+            [1.73759695, 0.313747346, 1.89878101],
+            [0.013188707, 0.0623068142, 155.23629],
+        )
+    }
+    // Index at `wavelength` in nanometers, or at the d-line for RGB rays
+    pub fn at(&self, wavelength: Option<f64>) -> f64 {
+        let lambda = wavelength.unwrap_or(LAMBDA_D) / 1000.0;
+        let lambda2 = lambda * lambda;
+        match *self {
+            Ior::Constant(n) => n,
+            Ior::Cauchy { a, b } => a + b / lambda2,
+            Ior::Sellmeier { b, c } => (1.0
+                + b.iter()
+                    .zip(c.iter())
+                    .map(|(b, c)| b * lambda2 / (lambda2 - c))
+                    .sum::<f64>())
+            .sqrt(),
+        }
+    }
+}
+
+impl From<f64> for Ior {
+    fn from(n: f64) -> Self {
+        Ior::Constant(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ior;
+
+    #[test]
+    fn glasses() {
+        // Catalog values at the d-line
+        assert!((Ior::bk7().at(None) - 1.5168).abs() < 1e-4);
+        assert!((Ior::dense_flint().at(None) - 1.7847).abs() < 1e-4);
+        assert!((Ior::cauchy(1.5046, 0.0042).at(None) - 1.5168).abs() < 1e-3);
+        assert_eq!(Ior::from(1.5).at(Some(400.)), 1.5);
+
+        // Normal dispersion: blue bends more than red
+        for ior in [Ior::bk7(), Ior::dense_flint(), Ior::cauchy(1.5046, 0.0042)] {
+            assert!(ior.at(Some(450.)) > ior.at(Some(650.)));
+        }
+    }
+}
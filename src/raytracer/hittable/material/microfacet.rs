@@ -0,0 +1,492 @@
+use super::{Ior, Material, ScatterRecord};
+use crate::{
+    raytracer::{HitRecord, Ray},
+    vec3::{Color, Onb, Vec3},
+};
+use rand::{Rng, RngCore};
+use std::f64::consts::PI;
+
+// Below this alpha the lobes are too sharp to be evaluated and are handled
+// as perfectly smooth interfaces
+const SMOOTH_ALPHA: f64 = 1e-3;
+
+// Isotropic GGX/Trowbridge-Reitz microfacet distribution, in the local frame
+// where the macro surface normal is +z
+#[derive(Debug, Copy, Clone)]
+pub struct Ggx {
+    alpha: f64,
+}
+
+impl Ggx {
+    // Perceptual roughness in [0, 1] is squared into alpha
+    pub fn from_roughness(roughness: f64) -> Self {
+        Ggx {
+            alpha: roughness.clamp(0.0, 1.0).powi(2),
+        }
+    }
+    pub fn is_smooth(&self) -> bool {
+        self.alpha < SMOOTH_ALPHA
+    }
+    // Normal distribution
+    pub fn d(&self, wm: Vec3) -> f64 {
+        if wm.z <= 0.0 {
+            return 0.0;
+        }
+        let a2 = self.alpha * self.alpha;
+        let t = wm.z * wm.z * (a2 - 1.0) + 1.0;
+        a2 / (PI * t * t)
+    }
+    fn lambda(&self, w: Vec3) -> f64 {
+        let cos2 = w.z * w.z;
+        if cos2 == 0.0 {
+            return f64::INFINITY;
+        }
+        let tan2 = (1.0 - cos2).max(0.0) / cos2;
+        0.5 * ((1.0 + self.alpha * self.alpha * tan2).sqrt() - 1.0)
+    }
+    // Smith masking
+    pub fn g1(&self, w: Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(w))
+    }
+    // Height-correlated masking-shadowing
+    pub fn g(&self, wo: Vec3, wi: Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
+    }
+    // Density of the normals visible from `w`
+    pub fn visible_pdf(&self, w: Vec3, wm: Vec3) -> f64 {
+        if w.z == 0.0 {
+            return 0.0;
+        }
+        self.g1(w) / w.z.abs() * self.d(wm) * w.dot(wm).abs()
+    }
+    // Samples a normal visible from `w` (Heitz 2018), always facing +z
+    pub fn sample_visible(&self, w: Vec3, rng: &mut dyn RngCore) -> Vec3 {
+        let mut wh = Vec3::new(self.alpha * w.x, self.alpha * w.y, w.z).normalize();
+        if wh.z < 0.0 {
+            wh = -wh;
+        }
+        let len2 = wh.x * wh.x + wh.y * wh.y;
+        let t1 = if len2 > 0.0 {
+            Vec3::new(-wh.y, wh.x, 0.0) / len2.sqrt()
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let t2 = wh.cross(t1);
+
+        // Uniform disk sample, warped towards the visible half of the projection
+        let r = rng.gen_range(0.0_f64..1.0).sqrt();
+        let phi = 2.0 * PI * rng.gen_range(0.0..1.0);
+        let p1 = r * phi.cos();
+        let p2 = r * phi.sin();
+        let s = 0.5 * (1.0 + wh.z);
+        let p2 = (1.0 - s) * (1.0 - p1 * p1).sqrt() + s * p2;
+        let nh = p1 * t1 + p2 * t2 + (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt() * wh;
+
+        Vec3::new(self.alpha * nh.x, self.alpha * nh.y, nh.z.max(1e-6)).normalize()
+    }
+    // Reflection lobe, both directions above the surface. Samples `wi`
+    // given `wo`, along with the microfacet normal it was reflected on.
+    pub fn sample_reflection(&self, wo: Vec3, rng: &mut dyn RngCore) -> Option<(Vec3, Vec3)> {
+        let wm = self.sample_visible(wo, rng);
+        let wi = reflect(wo, wm);
+        if wi.z <= 0.0 {
+            return None;
+        }
+        Some((wi, wm))
+    }
+    // D G / (4 cos_o): the reflection lobe times cos_i without its Fresnel
+    // term, along with the half vector to evaluate it
+    pub fn reflection(&self, wi: Vec3, wo: Vec3) -> Option<(f64, Vec3)> {
+        if wi.z <= 0.0 || wo.z <= 0.0 {
+            return None;
+        }
+        let wm = (wi + wo).normalize();
+        Some((self.d(wm) * self.g(wo, wi) / (4.0 * wo.z), wm))
+    }
+    pub fn reflection_pdf(&self, wi: Vec3, wo: Vec3) -> f64 {
+        if wi.z <= 0.0 || wo.z <= 0.0 {
+            return 0.0;
+        }
+        let wm = (wi + wo).normalize();
+        self.visible_pdf(wo, wm) / (4.0 * wo.dot(wm).abs())
+    }
+}
+
+// Fresnel reflectance of a conductor with complex IOR eta + i k
+fn fresnel_conductor(cos_i: f64, eta: Color, k: Color) -> Color {
+    let channel = |eta: f64, k: f64| {
+        let cos2 = cos_i * cos_i;
+        let sin2 = 1.0 - cos2;
+        let t0 = eta * eta - k * k - sin2;
+        let a2_plus_b2 = (t0 * t0 + 4.0 * eta * eta * k * k).sqrt();
+        let t1 = a2_plus_b2 + cos2;
+        let a = (0.5 * (a2_plus_b2 + t0)).max(0.0).sqrt();
+        let t2 = 2.0 * cos_i * a;
+        let rs = (t1 - t2) / (t1 + t2);
+        let t3 = cos2 * a2_plus_b2 + sin2 * sin2;
+        let t4 = t2 * sin2;
+        let rp = rs * (t3 - t4) / (t3 + t4);
+        0.5 * (rp + rs)
+    };
+    Color::new(
+        channel(eta.x, k.x),
+        channel(eta.y, k.y),
+        channel(eta.z, k.z),
+    )
+}
+
+// Unpolarized Fresnel reflectance of a dielectric interface, `cos_i` being
+// negative on the inner side
+pub fn fresnel_dielectric(cos_i: f64, eta: f64) -> f64 {
+    let (cos_i, eta) = if cos_i < 0.0 {
+        (-cos_i, 1.0 / eta)
+    } else {
+        (cos_i, eta)
+    };
+    let sin2_t = (1.0 - cos_i * cos_i) / (eta * eta);
+    if sin2_t >= 1.0 {
+        return 1.0;
+    }
+    let cos_t = (1.0 - sin2_t).sqrt();
+    let r_parallel = (eta * cos_i - cos_t) / (eta * cos_i + cos_t);
+    let r_perpendicular = (cos_i - eta * cos_t) / (cos_i + eta * cos_t);
+    0.5 * (r_parallel * r_parallel + r_perpendicular * r_perpendicular)
+}
+
+// Refraction of `wi` through the interface with normal `n` and relative IOR
+// `eta` (inner over outer side of `n`), None on total internal reflection
+fn refract(wi: Vec3, n: Vec3, eta: f64) -> Option<Vec3> {
+    let (n, eta) = if n.dot(wi) < 0.0 {
+        (-n, 1.0 / eta)
+    } else {
+        (n, eta)
+    };
+    let cos_i = n.dot(wi);
+    let sin2_t = (1.0 - cos_i * cos_i).max(0.0) / (eta * eta);
+    if sin2_t >= 1.0 {
+        return None;
+    }
+    let cos_t = (1.0 - sin2_t).sqrt();
+    Some(-wi / eta + (cos_i / eta - cos_t) * n)
+}
+
+fn reflect(wo: Vec3, n: Vec3) -> Vec3 {
+    -wo + 2.0 * wo.dot(n) * n
+}
+
+// Rough metal
+#[derive(Debug, Copy, Clone)]
+pub struct Conductor {
+    eta: Color,
+    k: Color,
+    distribution: Ggx,
+}
+
+impl Conductor {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(eta: Color, k: Color, roughness: f64) -> Material {
+        Material::Conductor(Conductor {
+            eta,
+            k,
+            distribution: Ggx::from_roughness(roughness),
+        })
+    }
+    pub fn gold(roughness: f64) -> Material {
+        Self::new(
+            Color::new(0.143, 0.374, 1.442),
+            Color::new(3.983, 2.385, 1.603),
+            roughness,
+        )
+    }
+    pub fn copper(roughness: f64) -> Material {
+        Self::new(
+            Color::new(0.200, 0.924, 1.102),
+            Color::new(3.912, 2.452, 2.142),
+            roughness,
+        )
+    }
+    pub fn aluminium(roughness: f64) -> Material {
+        Self::new(
+            Color::new(1.657, 0.880, 0.521),
+            Color::new(9.224, 6.270, 4.837),
+            roughness,
+        )
+    }
+    pub(super) fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<ScatterRecord> {
+        let onb = Onb::from_w(rec.normal);
+        let wo = onb.to_local(-r_in.direction.normalize());
+        if wo.z <= 0.0 {
+            return Option::None;
+        }
+        if self.distribution.is_smooth() {
+            let wi = Vec3::new(-wo.x, -wo.y, wo.z);
+            return Option::Some(ScatterRecord::delta(
+                fresnel_conductor(wo.z, self.eta, self.k),
+                Ray::new(rec.p, onb.local(wi), r_in.time),
+            ));
+        }
+
+        let (wi, wm) = self.distribution.sample_reflection(wo, rng)?;
+        let pdf = self.distribution.reflection_pdf(wi, wo);
+        // D G F / (4 cos_o) over D G1 / (4 cos_o)
+        let weight = fresnel_conductor(wo.dot(wm).abs(), self.eta, self.k)
+            * (self.distribution.g(wo, wi) / self.distribution.g1(wo));
+        Option::Some(ScatterRecord::new(
+            weight,
+            Ray::new(rec.p, onb.local(wi), r_in.time),
+            pdf,
+        ))
+    }
+    pub(super) fn eval(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> Color {
+        let onb = Onb::from_w(rec.normal);
+        let (wi, wo) = (onb.to_local(wi.normalize()), onb.to_local(wo.normalize()));
+        if self.distribution.is_smooth() {
+            return Color::zeros();
+        }
+        match self.distribution.reflection(wi, wo) {
+            Some((f, wm)) => f * fresnel_conductor(wo.dot(wm).abs(), self.eta, self.k),
+            None => Color::zeros(),
+        }
+    }
+    pub(super) fn pdf(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> f64 {
+        let onb = Onb::from_w(rec.normal);
+        let (wi, wo) = (onb.to_local(wi.normalize()), onb.to_local(wo.normalize()));
+        if self.distribution.is_smooth() {
+            return 0.0;
+        }
+        self.distribution.reflection_pdf(wi, wo)
+    }
+}
+
+// Rough glass, Walter et al. 2007. Like `Dielectric` the radiance scaling by
+// eta^2 on refraction is left out, it cancels out for closed objects.
+#[derive(Debug, Copy, Clone)]
+pub struct RoughDielectric {
+    ir: Ior,
+    distribution: Ggx,
+}
+
+impl RoughDielectric {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(ir: impl Into<Ior>, roughness: f64) -> Material {
+        Material::RoughDielectric(Self::bsdf(ir, roughness))
+    }
+    // Bare BSDF, for materials layering it with other lobes
+    pub(super) fn bsdf(ir: impl Into<Ior>, roughness: f64) -> Self {
+        RoughDielectric {
+            ir: ir.into(),
+            distribution: Ggx::from_roughness(roughness),
+        }
+    }
+    // Frame around the outward normal, so that wo.z < 0 from inside
+    fn frame(rec: &HitRecord) -> Onb {
+        Onb::from_w(if rec.front_face {
+            rec.normal
+        } else {
+            -rec.normal
+        })
+    }
+    // Relative IOR across the interface as seen from `wo`, `ir` being the
+    // index of the glass at the wavelength of the ray
+    fn eta(ir: f64, wo: Vec3) -> f64 {
+        if wo.z > 0.0 {
+            ir
+        } else {
+            1.0 / ir
+        }
+    }
+    pub(super) fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<ScatterRecord> {
+        let ir = self.ir.at(rec.wavelength);
+        let onb = Self::frame(rec);
+        let wo = onb.to_local(-r_in.direction.normalize());
+        if wo.z == 0.0 {
+            return Option::None;
+        }
+
+        if self.distribution.is_smooth() {
+            let n = Vec3::new(0., 0., 1.);
+            let reflectance = fresnel_dielectric(wo.z, ir);
+            let wi = if rng.gen_range(0.0..1.0) < reflectance {
+                reflect(wo, n)
+            } else {
+                refract(wo, n, ir)?
+            };
+            return Option::Some(ScatterRecord::delta(
+                Color::ones(),
+                Ray::new(rec.p, onb.local(wi), r_in.time),
+            ));
+        }
+
+        let wm = self.distribution.sample_visible(wo, rng);
+        let reflectance = fresnel_dielectric(wo.dot(wm), ir);
+        let wi = if rng.gen_range(0.0..1.0) < reflectance {
+            let wi = reflect(wo, wm);
+            if wi.z * wo.z <= 0.0 {
+                return Option::None;
+            }
+            wi
+        } else {
+            let wi = refract(wo, wm, ir)?;
+            if wi.z * wo.z >= 0.0 {
+                return Option::None;
+            }
+            wi
+        };
+
+        let pdf = self.local_pdf(ir, wi, wo);
+        if pdf <= 0.0 {
+            return Option::None;
+        }
+        Option::Some(ScatterRecord::new(
+            self.local_eval(ir, wi, wo) / pdf,
+            Ray::new(rec.p, onb.local(wi), r_in.time),
+            pdf,
+        ))
+    }
+    // Generalized half vector, facing +z, or None for degenerate and
+    // back facing configurations
+    fn half_vector(ir: f64, wi: Vec3, wo: Vec3) -> Option<(Vec3, bool)> {
+        if wi.z == 0.0 || wo.z == 0.0 {
+            return None;
+        }
+        let reflection = wi.z * wo.z > 0.0;
+        let etap = if reflection { 1.0 } else { Self::eta(ir, wo) };
+        let wm = wi * etap + wo;
+        if wm.near_zero() {
+            return None;
+        }
+        let wm = wm.normalize();
+        let wm = if wm.z < 0.0 { -wm } else { wm };
+        if wm.dot(wi) * wi.z < 0.0 || wm.dot(wo) * wo.z < 0.0 {
+            return None;
+        }
+        Some((wm, reflection))
+    }
+    fn local_eval(&self, ir: f64, wi: Vec3, wo: Vec3) -> Color {
+        let (wm, reflection) = match Self::half_vector(ir, wi, wo) {
+            Some(half) => half,
+            None => return Color::zeros(),
+        };
+        let d = self.distribution.d(wm);
+        let g = self.distribution.g(wo, wi);
+        let reflectance = fresnel_dielectric(wo.dot(wm), ir);
+        let f = if reflection {
+            d * g * reflectance / (4.0 * wo.z.abs())
+        } else {
+            let etap = Self::eta(ir, wo);
+            let denom = wi.dot(wm) + wo.dot(wm) / etap;
+            d * g * (1.0 - reflectance) * (wi.dot(wm) * wo.dot(wm)).abs()
+                / (wo.z.abs() * denom * denom)
+        };
+        Color::ones() * f
+    }
+    fn local_pdf(&self, ir: f64, wi: Vec3, wo: Vec3) -> f64 {
+        let (wm, reflection) = match Self::half_vector(ir, wi, wo) {
+            Some(half) => half,
+            None => return 0.0,
+        };
+        let reflectance = fresnel_dielectric(wo.dot(wm), ir);
+        let visible = self.distribution.visible_pdf(wo, wm);
+        if reflection {
+            visible / (4.0 * wo.dot(wm).abs()) * reflectance
+        } else {
+            let etap = Self::eta(ir, wo);
+            let denom = wi.dot(wm) + wo.dot(wm) / etap;
+            visible * wi.dot(wm).abs() / (denom * denom) * (1.0 - reflectance)
+        }
+    }
+    pub(super) fn eval(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> Color {
+        if self.distribution.is_smooth() {
+            return Color::zeros();
+        }
+        let onb = Self::frame(rec);
+        let ir = self.ir.at(rec.wavelength);
+        self.local_eval(
+            ir,
+            onb.to_local(wi.normalize()),
+            onb.to_local(wo.normalize()),
+        )
+    }
+    pub(super) fn pdf(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> f64 {
+        if self.distribution.is_smooth() {
+            return 0.0;
+        }
+        let onb = Self::frame(rec);
+        let ir = self.ir.at(rec.wavelength);
+        self.local_pdf(
+            ir,
+            onb.to_local(wi.normalize()),
+            onb.to_local(wo.normalize()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fresnel_conductor, fresnel_dielectric, Ggx};
+    use crate::vec3::{Color, Onb, Vec3};
+    use rand::{thread_rng, Rng};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn ggx_normalization() {
+        // The projected area of the microfacets equals the macro surface:
+        // integral of D(m) cos(m) over the hemisphere is one
+        let mut rng = thread_rng();
+        for roughness in [0.3, 0.6, 1.0] {
+            let ggx = Ggx::from_roughness(roughness);
+            let n = 200_000;
+            let mut sum = 0.0;
+            for _ in 0..n {
+                let wm = Vec3::rand_cosine_direction(&mut rng);
+                // Cosine sampling divides by cos / pi
+                sum += ggx.d(wm) * PI;
+            }
+            let integral = sum / n as f64;
+            assert!((integral - 1.0).abs() < 0.05, "{}", integral);
+        }
+    }
+
+    #[test]
+    fn visible_normals_are_normalized() {
+        let mut rng = thread_rng();
+        let ggx = Ggx::from_roughness(0.5);
+        let wo = Vec3::new(0.6, 0.0, 0.8);
+        let onb = Onb::from_w(Vec3::new(0., 0., 1.));
+        let n = 200_000;
+        let mut sum = 0.0;
+        for _ in 0..n {
+            let z: f64 = rng.gen_range(0.0..1.0);
+            let phi = rng.gen_range(0.0..2.0 * PI);
+            let r = (1.0 - z * z).sqrt();
+            let wm = onb.local(Vec3::new(r * phi.cos(), r * phi.sin(), z));
+            sum += ggx.visible_pdf(wo, wm) * 2.0 * PI;
+        }
+        let integral = sum / n as f64;
+        assert!((integral - 1.0).abs() < 0.05, "{}", integral);
+    }
+
+    #[test]
+    fn fresnel() {
+        // Normal incidence on glass
+        assert!((fresnel_dielectric(1.0, 1.5) - 0.04).abs() < 1e-12);
+        // Total internal reflection
+        assert_eq!(fresnel_dielectric(-0.2, 1.5), 1.0);
+        // Grazing angles reflect everything
+        let grazing = fresnel_conductor(1e-6, Color::new(0.2, 0.9, 1.1), Color::new(3.9, 2.5, 2.1));
+        assert!((grazing - Color::ones()).length() < 1e-3);
+        // A conductor without absorption is a dielectric
+        let r = fresnel_conductor(0.7, Color::ones() * 1.5, Color::zeros());
+        assert!((r.x - fresnel_dielectric(0.7, 1.5)).abs() < 1e-9);
+    }
+}
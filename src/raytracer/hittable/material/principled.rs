@@ -0,0 +1,247 @@
+use super::{
+    microfacet::{Ggx, RoughDielectric},
+    Ior, Material, ScatterRecord,
+};
+use crate::{
+    raytracer::{hittable::texture::Texture, HitRecord, Ray},
+    vec3::{Color, Onb, Vec3},
+};
+use rand::{Rng, RngCore};
+use std::f64::consts::PI;
+
+// Microfacet lobes get too sharp to be mixed with the others below this
+const MIN_ROUGHNESS: f64 = 0.05;
+
+// Disney-style principled BSDF: a diffuse base with sheen, a specular layer
+// going from dielectric to metallic, rough transmission and a clear coat
+#[derive(Debug, Copy, Clone)]
+pub struct Principled {
+    base_color: Texture,
+    metallic: Texture,
+    roughness: Texture,
+    specular: Texture,
+    clearcoat: Texture,
+    clearcoat_roughness: Texture,
+    sheen: Texture,
+    transmission: Texture,
+    ir: Ior,
+}
+
+// Parameters looked up for one shading point
+struct Lobes {
+    base_color: Color,
+    metallic: f64,
+    specular: Ggx,
+    f0: Color,
+    clearcoat: f64,
+    coat: Ggx,
+    sheen: f64,
+    transmission: f64,
+    glass: RoughDielectric,
+}
+
+// Probabilities to sample each lobe
+struct LobeWeights {
+    diffuse: f64,
+    specular: f64,
+    clearcoat: f64,
+    transmission: f64,
+}
+
+fn schlick(f0: Color, cosine: f64) -> Color {
+    f0 + (Color::ones() - f0) * (1.0 - cosine).clamp(0.0, 1.0).powi(5)
+}
+
+// Principled material being set up, from the defaults of `builder` on
+#[derive(Debug, Copy, Clone)]
+pub struct PrincipledBuilder(Principled);
+
+impl PrincipledBuilder {
+    pub fn metallic(mut self, metallic: impl Into<Texture>) -> Self {
+        self.0.metallic = metallic.into();
+        self
+    }
+    pub fn roughness(mut self, roughness: impl Into<Texture>) -> Self {
+        self.0.roughness = roughness.into();
+        self
+    }
+    // Dielectric reflectance at normal incidence, 0.5 being 4%
+    pub fn specular(mut self, specular: impl Into<Texture>) -> Self {
+        self.0.specular = specular.into();
+        self
+    }
+    pub fn clearcoat(
+        mut self,
+        clearcoat: impl Into<Texture>,
+        roughness: impl Into<Texture>,
+    ) -> Self {
+        self.0.clearcoat = clearcoat.into();
+        self.0.clearcoat_roughness = roughness.into();
+        self
+    }
+    pub fn sheen(mut self, sheen: impl Into<Texture>) -> Self {
+        self.0.sheen = sheen.into();
+        self
+    }
+    pub fn transmission(mut self, transmission: impl Into<Texture>, ir: impl Into<Ior>) -> Self {
+        self.0.transmission = transmission.into();
+        self.0.ir = ir.into();
+        self
+    }
+    pub fn build(self) -> Material {
+        Material::Principled(self.0)
+    }
+}
+
+impl Principled {
+    pub fn builder(base_color: impl Into<Texture>) -> PrincipledBuilder {
+        PrincipledBuilder(Principled {
+            base_color: base_color.into(),
+            metallic: 0.0.into(),
+            roughness: 0.5.into(),
+            specular: 0.5.into(),
+            clearcoat: 0.0.into(),
+            clearcoat_roughness: 0.1.into(),
+            sheen: 0.0.into(),
+            transmission: 0.0.into(),
+            ir: 1.5.into(),
+        })
+    }
+    fn lobes(&self, rec: &HitRecord) -> Lobes {
+        let (u, v, p) = (rec.u, rec.v, rec.p);
+        let base_color = self.base_color.value(u, v, p);
+        let metallic = self.metallic.scalar(u, v, p).clamp(0.0, 1.0);
+        let roughness = self.roughness.scalar(u, v, p).max(MIN_ROUGHNESS);
+        let specular = self.specular.scalar(u, v, p).max(0.0);
+        Lobes {
+            base_color,
+            metallic,
+            specular: Ggx::from_roughness(roughness),
+            f0: (1.0 - metallic) * 0.08 * specular * Color::ones() + metallic * base_color,
+            clearcoat: self.clearcoat.scalar(u, v, p).clamp(0.0, 1.0),
+            coat: Ggx::from_roughness(self.clearcoat_roughness.scalar(u, v, p).max(MIN_ROUGHNESS)),
+            sheen: self.sheen.scalar(u, v, p).max(0.0),
+            transmission: self.transmission.scalar(u, v, p).clamp(0.0, 1.0),
+            glass: RoughDielectric::bsdf(self.ir, roughness),
+        }
+    }
+    fn weights(lobes: &Lobes, cos_o: f64) -> Option<LobeWeights> {
+        let dielectric = 1.0 - lobes.metallic;
+        let diffuse = dielectric
+            * (1.0 - lobes.transmission)
+            * (lobes.base_color.luminance() + lobes.sheen).max(0.01);
+        let specular = schlick(lobes.f0, cos_o).luminance();
+        let clearcoat = 0.25 * lobes.clearcoat * schlick(0.04 * Color::ones(), cos_o).x;
+        let transmission = dielectric * lobes.transmission;
+
+        let total = diffuse + specular + clearcoat + transmission;
+        if total <= 0.0 {
+            return None;
+        }
+        Some(LobeWeights {
+            diffuse: diffuse / total,
+            specular: specular / total,
+            clearcoat: clearcoat / total,
+            transmission: transmission / total,
+        })
+    }
+    // BSDF times cos_i in the shading frame, `wi` and `wo` being given in
+    // world space as well for the transmission lobe
+    fn local_eval(&self, lobes: &Lobes, rec: &HitRecord, onb: &Onb, wi: Vec3, wo: Vec3) -> Color {
+        let (wi_local, wo_local) = (onb.to_local(wi), onb.to_local(wo));
+        let dielectric = 1.0 - lobes.metallic;
+        let mut f = Color::zeros();
+
+        if wi_local.z > 0.0 {
+            f += dielectric * (1.0 - lobes.transmission) * lobes.base_color * wi_local.z / PI;
+            let wm = (wi_local + wo_local).normalize();
+            f += dielectric
+                * lobes.sheen
+                * (1.0 - wi_local.dot(wm)).powi(5)
+                * wi_local.z
+                * Color::ones();
+        }
+        if let Some((spec, wm)) = lobes.specular.reflection(wi_local, wo_local) {
+            f += spec * schlick(lobes.f0, wo_local.dot(wm));
+        }
+        if let Some((coat, wm)) = lobes.coat.reflection(wi_local, wo_local) {
+            f += 0.25 * lobes.clearcoat * coat * schlick(0.04 * Color::ones(), wo_local.dot(wm));
+        }
+        // Reflection off the glass is already covered by the specular lobe
+        if wi_local.z < 0.0 && lobes.transmission > 0.0 {
+            f += dielectric * lobes.transmission * lobes.base_color * lobes.glass.eval(rec, wi, wo);
+        }
+        f
+    }
+    fn local_pdf(
+        lobes: &Lobes,
+        weights: &LobeWeights,
+        rec: &HitRecord,
+        onb: &Onb,
+        wi: Vec3,
+        wo: Vec3,
+    ) -> f64 {
+        let (wi_local, wo_local) = (onb.to_local(wi), onb.to_local(wo));
+        let mut pdf = weights.diffuse * wi_local.z.max(0.0) / PI
+            + weights.specular * lobes.specular.reflection_pdf(wi_local, wo_local)
+            + weights.clearcoat * lobes.coat.reflection_pdf(wi_local, wo_local);
+        if weights.transmission > 0.0 {
+            pdf += weights.transmission * lobes.glass.pdf(rec, wi, wo);
+        }
+        pdf
+    }
+
+    pub(super) fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<ScatterRecord> {
+        let lobes = self.lobes(rec);
+        let onb = Onb::from_w(rec.normal);
+        let wo = -r_in.direction.normalize();
+        let wo_local = onb.to_local(wo);
+        let weights = Self::weights(&lobes, wo_local.z)?;
+
+        // Pick one lobe to sample, then weight the direction by all of them
+        let choice: f64 = rng.gen_range(0.0..1.0);
+        let wi = if choice < weights.diffuse {
+            onb.local(Vec3::rand_cosine_direction(rng))
+        } else if choice < weights.diffuse + weights.specular {
+            onb.local(lobes.specular.sample_reflection(wo_local, rng)?.0)
+        } else if choice < weights.diffuse + weights.specular + weights.clearcoat {
+            onb.local(lobes.coat.sample_reflection(wo_local, rng)?.0)
+        } else {
+            lobes
+                .glass
+                .scatter(r_in, rec, rng)?
+                .ray
+                .direction
+                .normalize()
+        };
+
+        let pdf = Self::local_pdf(&lobes, &weights, rec, &onb, wi, wo);
+        if pdf <= 0.0 {
+            return Option::None;
+        }
+        Option::Some(ScatterRecord::new(
+            self.local_eval(&lobes, rec, &onb, wi, wo) / pdf,
+            Ray::new(rec.p, wi, r_in.time),
+            pdf,
+        ))
+    }
+    pub(super) fn eval(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> Color {
+        let lobes = self.lobes(rec);
+        let onb = Onb::from_w(rec.normal);
+        self.local_eval(&lobes, rec, &onb, wi.normalize(), wo.normalize())
+    }
+    pub(super) fn pdf(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> f64 {
+        let lobes = self.lobes(rec);
+        let onb = Onb::from_w(rec.normal);
+        let (wi, wo) = (wi.normalize(), wo.normalize());
+        match Self::weights(&lobes, onb.to_local(wo).z) {
+            Some(weights) => Self::local_pdf(&lobes, &weights, rec, &onb, wi, wo),
+            None => 0.0,
+        }
+    }
+}
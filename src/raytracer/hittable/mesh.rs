@@ -0,0 +1,231 @@
+mod obj;
+mod subdivision;
+
+use super::{aabb::Aabb, bvh::BvhNode, material::Material, HitRecord, Hittable};
+use crate::{
+    raytracer::Ray,
+    vec3::{Point3, Vec3},
+};
+use rand::RngCore;
+use std::sync::Arc;
+
+// Polygon mesh sharing vertices between faces, each face listing the indices
+// of its vertices counter-clockwise seen from the outside
+#[derive(Debug, Clone, Default)]
+pub struct Mesh {
+    pub positions: Vec<Point3>,
+    pub faces: Vec<Vec<usize>>,
+}
+
+impl Mesh {
+    pub fn new(positions: Vec<Point3>, faces: Vec<Vec<usize>>) -> Self {
+        Mesh { positions, faces }
+    }
+    // Normals averaged over the faces around each vertex, weighted by their
+    // areas
+    pub fn vertex_normals(&self) -> Vec<Vec3> {
+        let mut normals = vec![Vec3::zeros(); self.positions.len()];
+        for face in &self.faces {
+            // Twice the vector area of the polygon, from a fan of triangles
+            let origin = self.positions[face[0]];
+            let mut area = Vec3::zeros();
+            for i in 1..face.len().saturating_sub(1) {
+                area +=
+                    (self.positions[face[i]] - origin).cross(self.positions[face[i + 1]] - origin);
+            }
+            for &vertex in face {
+                normals[vertex] += area;
+            }
+        }
+        normals
+            .into_iter()
+            .map(|n| if n.near_zero() { n } else { n.normalize() })
+            .collect()
+    }
+}
+
+// Vertices shared by the triangles of a mesh
+struct Vertices {
+    positions: Vec<Point3>,
+    normals: Vec<Vec3>,
+    material: Material,
+}
+
+// Triangle of a mesh, shaded with the normals of its vertices interpolated.
+// Its uv coordinates are the barycentric weights of its second and third
+// vertices.
+struct Triangle {
+    vertices: Arc<Vertices>,
+    indices: [usize; 3],
+}
+
+impl Hittable for Triangle {
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        let [a, b, c] = self.indices.map(|i| self.vertices.positions[i]);
+        let Some((t, beta, gamma)) = hit_triangle(r, a, b, c, t_min, t_max) else {
+            return false;
+        };
+        let material = &self.vertices.material;
+        let p = r.at(t);
+        if !material.is_opaque_at(r, t, beta, gamma, p) {
+            return false;
+        }
+        let [na, nb, nc] = self.indices.map(|i| self.vertices.normals[i]);
+        let mut out_normal = (1.0 - beta - gamma) * na + beta * nb + gamma * nc;
+        if out_normal.near_zero() {
+            out_normal = (b - a).cross(c - a);
+        }
+        rec.t = t;
+        rec.p = p;
+        (rec.u, rec.v) = (beta, gamma);
+        (rec.dpdu, rec.dpdv) = (b - a, c - a);
+        rec.set_face_normal(r, out_normal.normalize());
+        rec.material = material;
+        true
+    }
+
+    fn bounding_box(&self, _time_frame: (f64, f64)) -> Option<Aabb> {
+        let [a, b, c] = self.indices.map(|i| self.vertices.positions[i]);
+        let corners = Aabb::surronding_box(&Aabb::new(a, a), &Aabb::new(b, b));
+        let corners = Aabb::surronding_box(&corners, &Aabb::new(c, c));
+        Some(corners.with_volume())
+    }
+}
+
+// Mesh cut into triangles under a bounding volume hierarchy of its own,
+// smoothly shaded
+pub struct TriangleMesh {
+    triangles: BvhNode,
+}
+
+impl TriangleMesh {
+    pub fn new(mesh: &Mesh, material: Material, rng: &mut dyn RngCore) -> Self {
+        let vertices = Arc::new(Vertices {
+            positions: mesh.positions.clone(),
+            normals: mesh.vertex_normals(),
+            material,
+        });
+        // Polygons as fans of triangles
+        let mut triangles: Vec<Arc<dyn Hittable + Send + Sync>> = Vec::new();
+        for face in &mesh.faces {
+            for i in 1..face.len().saturating_sub(1) {
+                triangles.push(Arc::new(Triangle {
+                    vertices: vertices.clone(),
+                    indices: [face[0], face[i], face[i + 1]],
+                }));
+            }
+        }
+        assert!(!triangles.is_empty(), "A mesh needs at least one face");
+        TriangleMesh {
+            triangles: BvhNode::new(&triangles, rng, 0, triangles.len(), &(0.0, 0.0)),
+        }
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        self.triangles.hit(r, t_min, t_max, rec)
+    }
+
+    fn bounding_box(&self, time_frame: (f64, f64)) -> Option<Aabb> {
+        self.triangles.bounding_box(time_frame)
+    }
+}
+
+// Distance along `r` to the triangle abc, with the barycentric weights of b
+// and c at the hit point (Möller-Trumbore)
+pub(super) fn hit_triangle(
+    r: &Ray,
+    a: Point3,
+    b: Point3,
+    c: Point3,
+    t_min: f64,
+    t_max: f64,
+) -> Option<(f64, f64, f64)> {
+    let (e1, e2) = (b - a, c - a);
+    let p = r.direction.cross(e2);
+    let determinant = e1.dot(p);
+    if determinant == 0.0 {
+        return None;
+    }
+    let inverse = 1.0 / determinant;
+    let s = r.origin - a;
+    let beta = s.dot(p) * inverse;
+    if !(0.0..=1.0).contains(&beta) {
+        return None;
+    }
+    let q = s.cross(e1);
+    let gamma = r.direction.dot(q) * inverse;
+    if gamma < 0.0 || beta + gamma > 1.0 {
+        return None;
+    }
+    let t = e2.dot(q) * inverse;
+    if t < t_min || t_max < t {
+        return None;
+    }
+    Some((t, beta, gamma))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Mesh, TriangleMesh};
+    use crate::{
+        raytracer::{hittable::material::Material, HitRecord, Hittable, Ray},
+        vec3::{Point3, Vec3},
+    };
+
+    use rand::thread_rng;
+    // Unit square in the xz plane facing up, as two triangles
+    fn square() -> Mesh {
+        Mesh::new(
+            vec![
+                Point3::new(0., 0., 0.),
+                Point3::new(0., 0., 1.),
+                Point3::new(1., 0., 1.),
+                Point3::new(1., 0., 0.),
+            ],
+            vec![vec![0, 1, 2], vec![0, 2, 3]],
+        )
+    }
+
+    #[test]
+    fn normals() {
+        let normals = square().vertex_normals();
+        assert!(normals.iter().all(|&n| n == Vec3::up()));
+
+        // Corner of a cube, shared by three faces of the same area
+        let mesh = Mesh::new(
+            vec![
+                Point3::zeros(),
+                Point3::new(1., 0., 0.),
+                Point3::new(0., 1., 0.),
+                Point3::new(0., 0., 1.),
+                Point3::new(1., 1., 0.),
+                Point3::new(0., 1., 1.),
+                Point3::new(1., 0., 1.),
+            ],
+            vec![vec![0, 2, 4, 1], vec![0, 3, 5, 2], vec![0, 1, 6, 3]],
+        );
+        let corner = mesh.vertex_normals()[0];
+        assert!((corner - -Vec3::ones().normalize()).length() < 1e-12);
+    }
+
+    #[test]
+    fn hit() {
+        let mesh = TriangleMesh::new(&square(), Material::None, &mut thread_rng());
+        let mut rec = HitRecord::new();
+
+        let ray = Ray::new(Point3::new(0.75, 2., 0.25), -Vec3::up(), 0.0);
+        assert!(mesh.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!((rec.t, rec.normal), (2.0, Vec3::up()));
+        assert!(rec.front_face);
+        let ray = Ray::new(Point3::new(0.25, -2., 0.75), Vec3::up(), 0.0);
+        assert!(mesh.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert!(!rec.front_face);
+        let ray = Ray::new(Point3::new(1.25, 2., 0.75), -Vec3::up(), 0.0);
+        assert!(!mesh.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+
+        let bbox = mesh.bounding_box((0.0, 0.0)).unwrap();
+        assert!(bbox.minimum.y < 0.0 && bbox.maximum.y > 0.0);
+    }
+}
@@ -0,0 +1,88 @@
+use super::Mesh;
+use crate::vec3::Point3;
+use std::{fs, io, path::Path};
+
+impl Mesh {
+    // Vertices and faces of a Wavefront OBJ file, anything else is skipped
+    pub fn load_obj(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_obj(&fs::read_to_string(path)?)
+    }
+    pub fn from_obj(text: &str) -> io::Result<Self> {
+        let invalid = |line: usize, message: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Line {}: {}", line + 1, message),
+            )
+        };
+        let mut mesh = Mesh::default();
+        for (line, content) in text.lines().enumerate() {
+            let mut tokens = content.split_whitespace();
+            match tokens.next() {
+                Some("v") => {
+                    let coordinates = tokens
+                        .take(3)
+                        .map(|token| token.parse::<f64>())
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|_| invalid(line, "Invalid vertex"))?;
+                    let [x, y, z] = coordinates[..] else {
+                        return Err(invalid(line, "Vertex without 3 coordinates"));
+                    };
+                    mesh.positions.push(Point3::new(x, y, z));
+                }
+                Some("f") => {
+                    // Only the position of `v/vt/vn` references is kept,
+                    // negative ones counting back from the last vertex
+                    let mut face = Vec::new();
+                    for token in tokens {
+                        let index = token
+                            .split('/')
+                            .next()
+                            .and_then(|index| index.parse::<i64>().ok())
+                            .ok_or_else(|| invalid(line, "Invalid face"))?;
+                        let count = mesh.positions.len() as i64;
+                        let index = if index < 0 { count + index } else { index - 1 };
+                        if !(0..count).contains(&index) {
+                            return Err(invalid(line, "Face with an unknown vertex"));
+                        }
+                        face.push(index as usize);
+                    }
+                    if face.len() < 3 {
+                        return Err(invalid(line, "Face with less than 3 vertices"));
+                    }
+                    mesh.faces.push(face);
+                }
+                _ => {}
+            }
+        }
+        Ok(mesh)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Mesh;
+    use crate::vec3::Point3;
+
+    #[test]
+    fn parse() {
+        let obj = "# square and a triangle
+o shape
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1.5 0
+vt 0 0
+vn 0 0 1
+f 1/1/1 2/1/1 3/1/1 4/1/1
+f -4 -3 -1
+";
+        let mesh = Mesh::from_obj(obj).unwrap();
+        assert_eq!(mesh.positions.len(), 4);
+        assert_eq!(mesh.positions[3], Point3::new(0., 1.5, 0.));
+        assert_eq!(mesh.faces, vec![vec![0, 1, 2, 3], vec![0, 1, 3]]);
+
+        assert!(Mesh::from_obj("v 0 0").is_err());
+        assert!(Mesh::from_obj("v 0 0 0\nf 1 2 3").is_err());
+        assert!(Mesh::from_obj("v 0 0 0\nf 1 1").is_err());
+    }
+}
@@ -0,0 +1,383 @@
+use super::Mesh;
+use crate::vec3::{Point3, Vec3};
+use std::collections::HashMap;
+
+// Edge between two vertices, with the faces on either side of it
+struct Edge {
+    ends: (usize, usize),
+    faces: Vec<usize>,
+}
+
+// Connectivity of a mesh: its edges, numbered in the order faces go through
+// them, and what is around each vertex
+struct Topology {
+    edges: Vec<Edge>,
+    edge_index: HashMap<(usize, usize), usize>,
+    vertex_edges: Vec<Vec<usize>>,
+    vertex_faces: Vec<Vec<usize>>,
+}
+
+impl Topology {
+    fn new(mesh: &Mesh) -> Self {
+        let mut topology = Topology {
+            edges: Vec::new(),
+            edge_index: HashMap::new(),
+            vertex_edges: vec![Vec::new(); mesh.positions.len()],
+            vertex_faces: vec![Vec::new(); mesh.positions.len()],
+        };
+        for (f, face) in mesh.faces.iter().enumerate() {
+            for (i, &a) in face.iter().enumerate() {
+                let b = face[(i + 1) % face.len()];
+                let key = (a.min(b), a.max(b));
+                let edges = &mut topology.edges;
+                let e = *topology.edge_index.entry(key).or_insert_with(|| {
+                    edges.push(Edge {
+                        ends: key,
+                        faces: Vec::new(),
+                    });
+                    edges.len() - 1
+                });
+                if topology.edges[e].faces.is_empty() {
+                    topology.vertex_edges[a].push(e);
+                    topology.vertex_edges[b].push(e);
+                }
+                topology.edges[e].faces.push(f);
+                topology.vertex_faces[a].push(f);
+            }
+        }
+        topology
+    }
+    fn edge(&self, a: usize, b: usize) -> usize {
+        self.edge_index[&(a.min(b), a.max(b))]
+    }
+    fn is_boundary(&self, e: usize) -> bool {
+        self.edges[e].faces.len() == 1
+    }
+    fn other_end(&self, e: usize, v: usize) -> usize {
+        let (a, b) = self.edges[e].ends;
+        if a == v {
+            b
+        } else {
+            a
+        }
+    }
+    // Boundaries are refined as cubic B-splines, their vertices weighted
+    // 1/8, 3/4, 1/8 with their neighbors along the boundary, while corners
+    // of a single face stay in place. None for interior vertices.
+    fn boundary_point(&self, positions: &[Point3], v: usize) -> Option<Point3> {
+        let neighbors: Vec<usize> = self.vertex_edges[v]
+            .iter()
+            .filter(|&&e| self.is_boundary(e))
+            .map(|&e| self.other_end(e, v))
+            .collect();
+        match neighbors[..] {
+            [] => None,
+            [a, b] if self.vertex_faces[v].len() > 1 => {
+                Some(0.75 * positions[v] + 0.125 * (positions[a] + positions[b]))
+            }
+            _ => Some(positions[v]),
+        }
+    }
+}
+
+impl Mesh {
+    // Refines the mesh `levels` times, with Loop's scheme while it is made of
+    // triangles and Catmull-Clark's otherwise, which leaves quads only. Both
+    // converge to smooth surfaces.
+    pub fn subdivide(&self, levels: u32) -> Mesh {
+        let mut mesh = self.without_degenerate_faces();
+        for _ in 0..levels {
+            mesh = if mesh.faces.iter().all(|face| face.len() == 3) {
+                mesh.loop_step()
+            } else {
+                mesh.catmull_clark_step()
+            };
+        }
+        mesh
+    }
+    // Faces going through a vertex twice in a row have it once, and those
+    // left with less than 3 vertices or still repeating one are dropped:
+    // their edges would not separate two sides of the surface
+    fn without_degenerate_faces(&self) -> Mesh {
+        let faces = self
+            .faces
+            .iter()
+            .map(|face| {
+                let m = face.len();
+                (0..m)
+                    .filter(|&i| face[i] != face[(i + 1) % m])
+                    .map(|i| face[i])
+                    .collect::<Vec<usize>>()
+            })
+            .filter(|face| {
+                face.len() >= 3
+                    && face
+                        .iter()
+                        .enumerate()
+                        .all(|(i, v)| !face[i + 1..].contains(v))
+            })
+            .collect();
+        Mesh::new(self.positions.clone(), faces)
+    }
+    // Splits each triangle in four, new vertices coming after the old ones,
+    // in the order of the edges they split
+    fn loop_step(&self) -> Mesh {
+        let topology = Topology::new(self);
+        let p = &self.positions;
+        let n = p.len();
+
+        let mut positions: Vec<Point3> = (0..n)
+            .map(|v| {
+                topology.boundary_point(p, v).unwrap_or_else(|| {
+                    let neighbors = &topology.vertex_edges[v];
+                    let k = neighbors.len();
+                    if k == 0 {
+                        return p[v];
+                    }
+                    // Warren's weights
+                    let beta = if k == 3 {
+                        3.0 / 16.0
+                    } else {
+                        3.0 / (8.0 * k as f64)
+                    };
+                    let sum = neighbors
+                        .iter()
+                        .fold(Vec3::zeros(), |sum, &e| sum + p[topology.other_end(e, v)]);
+                    (1.0 - k as f64 * beta) * p[v] + beta * sum
+                })
+            })
+            .collect();
+        positions.extend(topology.edges.iter().map(|edge| {
+            let (a, b) = edge.ends;
+            match edge.faces[..] {
+                [f, g] => {
+                    let opposite = |f: usize| {
+                        let c = self.faces[f].iter().find(|&&c| c != a && c != b);
+                        p[*c.unwrap()]
+                    };
+                    0.375 * (p[a] + p[b]) + 0.125 * (opposite(f) + opposite(g))
+                }
+                _ => 0.5 * (p[a] + p[b]),
+            }
+        }));
+
+        let mut faces = Vec::with_capacity(4 * self.faces.len());
+        for face in &self.faces {
+            let [a, b, c] = [face[0], face[1], face[2]];
+            let [ab, bc, ca] = [(a, b), (b, c), (c, a)].map(|(u, v)| n + topology.edge(u, v));
+            faces.extend([
+                vec![a, ab, ca],
+                vec![ab, b, bc],
+                vec![ca, bc, c],
+                vec![ab, bc, ca],
+            ]);
+        }
+        Mesh::new(positions, faces)
+    }
+    // Splits each n-gon in n quads around its center, new vertices coming
+    // after the old ones, first those splitting edges then face centers
+    fn catmull_clark_step(&self) -> Mesh {
+        let topology = Topology::new(self);
+        let p = &self.positions;
+        let n = p.len();
+        let average = |points: &mut dyn Iterator<Item = Point3>| {
+            let (sum, count) = points.fold((Vec3::zeros(), 0), |(sum, count), point| {
+                (sum + point, count + 1)
+            });
+            sum / count as f64
+        };
+
+        let face_points: Vec<Point3> = self
+            .faces
+            .iter()
+            .map(|face| average(&mut face.iter().map(|&v| p[v])))
+            .collect();
+        let edge_points: Vec<Point3> = topology
+            .edges
+            .iter()
+            .map(|edge| {
+                let (a, b) = edge.ends;
+                match edge.faces[..] {
+                    [f, g] => 0.25 * (p[a] + p[b] + face_points[f] + face_points[g]),
+                    _ => 0.5 * (p[a] + p[b]),
+                }
+            })
+            .collect();
+        let mut positions: Vec<Point3> = (0..n)
+            .map(|v| {
+                topology.boundary_point(p, v).unwrap_or_else(|| {
+                    let edges = &topology.vertex_edges[v];
+                    let k = edges.len();
+                    if k == 0 {
+                        return p[v];
+                    }
+                    // (F + 2R + (k - 3) P) / k, F averaging the centers of
+                    // the faces around and R the midpoints of the edges
+                    let f = average(&mut topology.vertex_faces[v].iter().map(|&f| face_points[f]));
+                    let r = average(&mut edges.iter().map(|&e| {
+                        let (a, b) = topology.edges[e].ends;
+                        0.5 * (p[a] + p[b])
+                    }));
+                    (f + 2.0 * r + (k as f64 - 3.0) * p[v]) / k as f64
+                })
+            })
+            .collect();
+        positions.extend(edge_points);
+        positions.extend(face_points);
+
+        let centers = n + topology.edges.len();
+        let mut faces = Vec::new();
+        for (f, face) in self.faces.iter().enumerate() {
+            let m = face.len();
+            for i in 0..m {
+                let (previous, v, next) = (face[(i + m - 1) % m], face[i], face[(i + 1) % m]);
+                faces.push(vec![
+                    v,
+                    n + topology.edge(v, next),
+                    centers + f,
+                    n + topology.edge(previous, v),
+                ]);
+            }
+        }
+        Mesh::new(positions, faces)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Mesh, Topology};
+    use crate::vec3::{Point3, Vec3};
+
+    fn cube() -> Mesh {
+        let positions = (0..8)
+            .map(|i| {
+                let coordinate = |bit: u32| if i & (1 << bit) == 0 { -1.0 } else { 1.0 };
+                Point3::new(coordinate(0), coordinate(1), coordinate(2))
+            })
+            .collect();
+        let faces = vec![
+            vec![0, 2, 3, 1],
+            vec![4, 5, 7, 6],
+            vec![0, 1, 5, 4],
+            vec![2, 6, 7, 3],
+            vec![0, 4, 6, 2],
+            vec![1, 3, 7, 5],
+        ];
+        Mesh::new(positions, faces)
+    }
+
+    fn octahedron() -> Mesh {
+        let positions = vec![
+            Point3::new(1., 0., 0.),
+            Point3::new(-1., 0., 0.),
+            Point3::new(0., 1., 0.),
+            Point3::new(0., -1., 0.),
+            Point3::new(0., 0., 1.),
+            Point3::new(0., 0., -1.),
+        ];
+        let faces = vec![
+            vec![0, 2, 4],
+            vec![2, 1, 4],
+            vec![1, 3, 4],
+            vec![3, 0, 4],
+            vec![2, 0, 5],
+            vec![1, 2, 5],
+            vec![3, 1, 5],
+            vec![0, 3, 5],
+        ];
+        Mesh::new(positions, faces)
+    }
+
+    // Every edge between two faces, and normals pointing away from the
+    // center
+    fn assert_closed_and_outwards(mesh: &Mesh) {
+        let topology = Topology::new(mesh);
+        assert!(topology.edges.iter().all(|edge| edge.faces.len() == 2));
+        for (p, n) in mesh.positions.iter().zip(mesh.vertex_normals()) {
+            assert!(p.dot(n) > 0.0);
+        }
+    }
+
+    #[test]
+    fn loop_scheme() {
+        let mesh = octahedron().subdivide(1);
+        assert_eq!(mesh.positions.len(), 6 + 12);
+        assert_eq!(mesh.faces.len(), 32);
+        // Vertices of valence 4 are pulled in by 3/8, edges to 3/8 of the
+        // way to both of their ends
+        assert_eq!(mesh.positions[0], Point3::new(0.625, 0., 0.));
+        assert_eq!(mesh.positions[6], Point3::new(0.375, 0.375, 0.));
+        assert_closed_and_outwards(&mesh);
+
+        let smooth = octahedron().subdivide(3);
+        assert_eq!(smooth.faces.len(), 8 * 64);
+        assert!(smooth.faces.iter().all(|face| face.len() == 3));
+        assert_closed_and_outwards(&smooth);
+    }
+
+    #[test]
+    fn catmull_clark_scheme() {
+        let mesh = cube().subdivide(1);
+        assert_eq!(mesh.positions.len(), 8 + 12 + 6);
+        assert_eq!(mesh.faces.len(), 24);
+        let corner = 5.0 / 9.0;
+        assert!((mesh.positions[7] - Vec3::new(corner, corner, corner)).length() < 1e-12);
+        assert_closed_and_outwards(&mesh);
+
+        // Triangles and quads mixed are all turned into quads
+        let mut mixed = cube();
+        mixed.faces[1] = vec![4, 5, 7];
+        mixed.faces.push(vec![4, 7, 6]);
+        let smooth = mixed.subdivide(2);
+        assert!(smooth.faces.iter().all(|face| face.len() == 4));
+        assert_closed_and_outwards(&smooth);
+    }
+
+    #[test]
+    fn boundaries() {
+        // An open square keeps its corners and edges
+        let square = Mesh::new(
+            vec![
+                Point3::new(0., 0., 0.),
+                Point3::new(0., 0., 1.),
+                Point3::new(1., 0., 1.),
+                Point3::new(1., 0., 0.),
+            ],
+            vec![vec![0, 1, 2, 3]],
+        );
+        let mesh = square.subdivide(3);
+        assert_eq!(mesh.positions[..4], square.positions[..]);
+        for p in &mesh.positions {
+            assert_eq!(p.y, 0.0);
+            assert!((0.0..=1.0).contains(&p.x) && (0.0..=1.0).contains(&p.z));
+        }
+        assert!(mesh
+            .positions
+            .iter()
+            .any(|p| p.x == 0.0 && (p.z - 0.25).abs() < 1e-12));
+    }
+
+    #[test]
+    fn degenerate_faces() {
+        // A face going through a vertex twice is left out, though its edges
+        // look shared by two faces
+        let obj = "v 0 0 0
+v 1 0 0
+v 0 1 0
+v 0 0 1
+f 2 3 4
+f 1 1 2
+";
+        let mesh = Mesh::from_obj(obj).unwrap();
+        let smooth = mesh.subdivide(1);
+        assert_eq!(smooth.faces.len(), 4);
+        assert!(smooth.faces.iter().all(|face| face.len() == 3));
+
+        // Repeats in a row are merged, leaving a triangle next to the quad
+        let mut mixed = mesh.clone();
+        mixed.faces = vec![vec![0, 1, 2, 3], vec![0, 3, 3, 2], vec![1, 2, 1, 3]];
+        let smooth = mixed.subdivide(1);
+        assert_eq!(smooth.faces.len(), 4 + 3);
+        assert!(smooth.faces.iter().all(|face| face.len() == 4));
+    }
+}
@@ -0,0 +1,160 @@
+use super::{aabb::Aabb, texture::Texture, HitRecord, Hittable};
+use crate::{
+    raytracer::Ray,
+    vec3::{Onb, Point3, Vec3},
+};
+use std::sync::Arc;
+
+// Step in uv used to differentiate bump maps
+const BUMP_DELTA: f64 = 0.0005;
+
+// Way the shading normal departs from the geometric one
+#[derive(Debug, Copy, Clone)]
+enum ShadingNormal {
+    // Tangent-space normal encoded in [0, 1] as in usual normal map images:
+    // red along dpdu, green along the bitangent and blue along the normal
+    Map(Texture),
+    // Surface displaced along its normal by `scale` times the texture
+    Bump { height: Texture, scale: f64 },
+}
+
+// Wraps an object to perturb the normal of its hits, leaving the geometry
+// itself untouched
+pub struct Perturbed {
+    object: Arc<dyn Hittable + Send + Sync>,
+    shading: ShadingNormal,
+}
+
+impl Perturbed {
+    pub fn normal_map(object: Arc<dyn Hittable + Send + Sync>, map: impl Into<Texture>) -> Self {
+        Perturbed {
+            object,
+            shading: ShadingNormal::Map(map.into()),
+        }
+    }
+    pub fn bump(
+        object: Arc<dyn Hittable + Send + Sync>,
+        height: impl Into<Texture>,
+        scale: f64,
+    ) -> Self {
+        Perturbed {
+            object,
+            shading: ShadingNormal::Bump {
+                height: height.into(),
+                scale,
+            },
+        }
+    }
+    // Perturbed outward normal at `rec`, None where the surface has no
+    // tangents to perturb along
+    fn shading_normal(&self, rec: &HitRecord, outward: Vec3) -> Option<Vec3> {
+        match self.shading {
+            ShadingNormal::Map(map) => {
+                let tangent = rec.dpdu - rec.dpdu.dot(outward) * outward;
+                let tangent = if tangent.length_squared() > 0.0 {
+                    tangent.normalize()
+                } else {
+                    Onb::from_w(outward).u
+                };
+                let bitangent = outward.cross(tangent);
+                let m = 2.0 * map.value(rec.u, rec.v, rec.p) - Vec3::ones();
+                Some((m.x * tangent + m.y * bitangent + m.z * outward).normalize())
+            }
+            ShadingNormal::Bump { height, scale } => {
+                if rec.dpdu.length_squared() == 0.0 || rec.dpdv.length_squared() == 0.0 {
+                    return None;
+                }
+                let h = |u: f64, v: f64, p: Point3| scale * height.scalar(u, v, p);
+                let h0 = h(rec.u, rec.v, rec.p);
+                let dhdu =
+                    (h(rec.u + BUMP_DELTA, rec.v, rec.p + BUMP_DELTA * rec.dpdu) - h0) / BUMP_DELTA;
+                let dhdv =
+                    (h(rec.u, rec.v + BUMP_DELTA, rec.p + BUMP_DELTA * rec.dpdv) - h0) / BUMP_DELTA;
+
+                let normal = (rec.dpdu + dhdu * outward)
+                    .cross(rec.dpdv + dhdv * outward)
+                    .normalize();
+                Some(if normal.dot(outward) < 0.0 {
+                    -normal
+                } else {
+                    normal
+                })
+            }
+        }
+    }
+}
+
+impl Hittable for Perturbed {
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        if !self.object.hit(r, t_min, t_max, rec) {
+            return false;
+        }
+        let outward = if rec.front_face {
+            rec.normal
+        } else {
+            -rec.normal
+        };
+        if let Some(normal) = self.shading_normal(rec, outward) {
+            rec.normal = if rec.front_face { normal } else { -normal };
+        }
+        true
+    }
+
+    fn bounding_box(&self, time_frame: (f64, f64)) -> Option<Aabb> {
+        self.object.bounding_box(time_frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Perturbed;
+    use crate::{
+        raytracer::{
+            hittable::{material::Material, texture::Texture},
+            AaRect, HitRecord, Hittable, Ray, Sphere,
+        },
+        vec3::{Color, Point3, Vec3},
+    };
+    use std::sync::Arc;
+
+    fn normal_at(object: &dyn Hittable, ray: &Ray) -> Vec3 {
+        let mut rec = HitRecord::new();
+        assert!(object.hit(ray, 0.001, f64::INFINITY, &mut rec));
+        rec.normal
+    }
+
+    #[test]
+    fn flat_maps() {
+        let sphere = Arc::new(Sphere::new(Point3::zeros(), 1., Material::None));
+        let ray = Ray::new(Point3::new(0.3, 0.2, 5.), Vec3::new(0., 0., -1.), 0.0);
+        let expected = normal_at(sphere.as_ref(), &ray);
+
+        // Constant heights and straight up normals leave the normal as is
+        let bumped = Perturbed::bump(sphere.clone(), 0.7, 0.1);
+        let mapped = Perturbed::normal_map(sphere, Color::new(0.5, 0.5, 1.0));
+        for object in [&bumped as &dyn Hittable, &mapped] {
+            assert!((normal_at(object, &ray) - expected).length() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn tilted_maps() {
+        // Floor with u along x and v along z
+        let floor = Arc::new(AaRect::xz(0., 10., 0., 10., 0., Material::None));
+        let ray = Ray::new(Point3::new(2., 1., 3.), Vec3::new(0., -1., 0.), 0.0);
+
+        // Normal map tilting towards dpdu
+        let tilt = Color::new(0.5 + 0.5 * 0.6, 0.5, 0.5 + 0.5 * 0.8);
+        let mapped = Perturbed::normal_map(floor.clone(), tilt);
+        assert!((normal_at(&mapped, &ray) - Vec3::new(0.6, 0.8, 0.)).length() < 1e-9);
+
+        // Bumps tilt the surface the same way whichever side it is seen from
+        let bumped = Perturbed::bump(floor, Texture::noise(1.0), 0.5);
+        let from_below = Ray::new(Point3::new(2.3, -1., 3.6), Vec3::up(), 0.0);
+        let from_above = Ray::new(Point3::new(2.3, 1., 3.6), -Vec3::up(), 0.0);
+        let below = normal_at(&bumped, &from_below);
+        let above = normal_at(&bumped, &from_above);
+        assert!((below + above).length() < 1e-9);
+        assert!(above.y > 0.0 && (above - Vec3::up()).length() > 1e-3);
+    }
+}
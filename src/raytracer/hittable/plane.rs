@@ -0,0 +1,78 @@
+use super::{aabb::Aabb, material::Material, HitRecord, Hittable};
+use crate::{
+    raytracer::Ray,
+    vec3::{Onb, Point3, Vec3},
+};
+
+// Infinite plane through `point`, facing `normal`. Its uv coordinates are
+// distances along two tangents, so textures repeat across it.
+pub struct Plane {
+    point: Point3,
+    frame: Onb,
+    material: Material,
+}
+
+impl Plane {
+    pub fn new(point: Point3, normal: Vec3, material: Material) -> Self {
+        Plane {
+            point,
+            frame: Onb::from_w(normal),
+            material,
+        }
+    }
+}
+
+impl Hittable for Plane {
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        let denominator = r.direction.dot(self.frame.w);
+        if denominator == 0.0 {
+            return false;
+        }
+        let t = (self.point - r.origin).dot(self.frame.w) / denominator;
+        if !(t_min..t_max).contains(&t) {
+            return false;
+        }
+        let p = r.at(t);
+        let local = self.frame.to_local(p - self.point);
+        if !self.material.is_opaque_at(r, t, local.x, local.y, p) {
+            return false;
+        }
+        rec.t = t;
+        rec.p = p;
+        (rec.u, rec.v) = (local.x, local.y);
+        (rec.dpdu, rec.dpdv) = (self.frame.u, self.frame.v);
+        rec.set_face_normal(r, self.frame.w);
+        rec.material = &self.material;
+        true
+    }
+
+    fn bounding_box(&self, _time_frame: (f64, f64)) -> Option<Aabb> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Plane;
+    use crate::{
+        raytracer::{hittable::material::Material, HitRecord, Hittable, Ray},
+        vec3::{Point3, Vec3},
+    };
+
+    #[test]
+    fn hit() {
+        let plane = Plane::new(Point3::new(0., -1., 0.), Vec3::up(), Material::None);
+        let mut rec = HitRecord::new();
+
+        let ray = Ray::new(Point3::new(100., 1., -50.), Vec3::new(0., -2., 0.), 0.0);
+        assert!(plane.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!(rec.t, 1.0);
+        assert_eq!(rec.normal, Vec3::up());
+        assert!(rec.front_face);
+        assert_eq!(rec.dpdu.cross(rec.dpdv), Vec3::up());
+
+        let parallel = Ray::new(Point3::zeros(), Vec3::new(1., 0., 0.), 0.0);
+        assert!(!plane.hit(&parallel, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!(plane.bounding_box((0.0, 1.0)), None);
+    }
+}
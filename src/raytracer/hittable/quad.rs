@@ -0,0 +1,161 @@
+use super::{
+    aabb::Aabb, light::Light, material::Material, planar_pdf_value, HitRecord, Hittable,
+    HittableList,
+};
+use crate::{
+    raytracer::Ray,
+    vec3::{Point3, Vec3},
+};
+use rand::{Rng, RngCore};
+use std::sync::Arc;
+
+// Parallelogram spanned by the edges `u` and `v` from the corner `q`,
+// facing u x v. Its uv coordinates are the fractions of both edges.
+pub struct Quad {
+    q: Point3,
+    u: Vec3,
+    v: Vec3,
+    normal: Vec3,
+    // u x v over its squared length, to find the coordinates of hit points
+    w: Vec3,
+    area: f64,
+    material: Material,
+}
+
+impl Quad {
+    pub fn new(q: Point3, u: Vec3, v: Vec3, material: Material) -> Self {
+        let n = u.cross(v);
+        Quad {
+            q,
+            u,
+            v,
+            normal: n.normalize(),
+            w: n / n.length_squared(),
+            area: n.length(),
+            material,
+        }
+    }
+    // Box spanned by the edges `u`, `v` and `w` from `corner`, as six quads
+    // facing outwards
+    pub fn cuboid(corner: Point3, u: Vec3, v: Vec3, w: Vec3, material: Material) -> HittableList {
+        let (u, v) = if u.cross(v).dot(w) < 0.0 {
+            (v, u)
+        } else {
+            (u, v)
+        };
+        let mut sides = HittableList::new();
+        for (q, a, b) in [
+            (corner, v, u),
+            (corner + w, u, v),
+            (corner, w, v),
+            (corner + u, v, w),
+            (corner, u, w),
+            (corner + v, w, u),
+        ] {
+            sides.add(Arc::new(Quad::new(q, a, b, material.clone())));
+        }
+        sides
+    }
+}
+
+impl Hittable for Quad {
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        let denominator = r.direction.dot(self.normal);
+        if denominator == 0.0 {
+            return false;
+        }
+        let t = (self.q - r.origin).dot(self.normal) / denominator;
+        if !(t_min..t_max).contains(&t) {
+            return false;
+        }
+        let p = r.at(t);
+        let planar = p - self.q;
+        let alpha = self.w.dot(planar.cross(self.v));
+        let beta = self.w.dot(self.u.cross(planar));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return false;
+        }
+        if !self.material.is_opaque_at(r, t, alpha, beta, p) {
+            return false;
+        }
+        rec.t = t;
+        rec.p = p;
+        (rec.u, rec.v) = (alpha, beta);
+        (rec.dpdu, rec.dpdv) = (self.u, self.v);
+        rec.set_face_normal(r, self.normal);
+        rec.material = &self.material;
+        true
+    }
+
+    fn bounding_box(&self, _time_frame: (f64, f64)) -> Option<Aabb> {
+        let corners = Aabb::surronding_box(
+            &Aabb::new(self.q, self.q + self.u + self.v),
+            &Aabb::new(self.q + self.u, self.q + self.v),
+        );
+        Some(corners.with_volume())
+    }
+}
+
+impl Light for Quad {
+    fn pdf_value(&self, r: &Ray) -> f64 {
+        planar_pdf_value(self, self.area, r)
+    }
+
+    fn random(&self, origin: Point3, _time: f64, rng: &mut dyn RngCore) -> Vec3 {
+        let random_point =
+            self.q + rng.gen_range(0.0..1.0) * self.u + rng.gen_range(0.0..1.0) * self.v;
+        random_point - origin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Quad;
+    use crate::{
+        raytracer::{hittable::material::Material, HitRecord, Hittable, Ray},
+        vec3::{Point3, Vec3},
+    };
+    use rand::thread_rng;
+
+    #[test]
+    fn hit() {
+        // Slanted parallelogram
+        let quad = Quad::new(
+            Point3::new(0., 0., -2.),
+            Vec3::new(2., 0., 0.),
+            Vec3::new(1., 1., 0.),
+            Material::None,
+        );
+        let mut rec = HitRecord::new();
+
+        let ray = Ray::new(Point3::new(1.5, 0.5, 0.), Vec3::new(0., 0., -1.), 0.0);
+        assert!(quad.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!(rec.t, 2.0);
+        assert!(rec.front_face);
+        assert!((rec.u - 0.5).abs() < 1e-12 && (rec.v - 0.5).abs() < 1e-12);
+
+        // Inside its bounding rectangle but outside the parallelogram
+        let ray = Ray::new(Point3::new(0.2, 0.8, 0.), Vec3::new(0., 0., -1.), 0.0);
+        assert!(!quad.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+    }
+
+    #[test]
+    fn cuboid_faces_outwards() {
+        // Left-handed edges still give a closed box facing outwards
+        let sides = Quad::cuboid(
+            Point3::zeros(),
+            Vec3::new(1., 1., 0.),
+            Vec3::new(-1., 1., 0.),
+            Vec3::new(0., 0., -1.),
+            Material::None,
+        );
+        let center = Point3::new(0., 1., -0.5);
+        let mut rng = thread_rng();
+        for _ in 0..100 {
+            let ray = Ray::new(center, Vec3::rand_unit(&mut rng), 0.0);
+            let mut rec = HitRecord::new();
+            assert!(sides.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+            assert!(!rec.front_face);
+        }
+    }
+}
@@ -0,0 +1,386 @@
+use super::{aabb::Aabb, material::Material, HitRecord, Hittable};
+use crate::{
+    raytracer::Ray,
+    vec3::{Onb, Point3, Vec3},
+};
+use std::{fmt::Debug, sync::Arc};
+
+// Distance under which the march has reached the surface
+const EPSILON: f64 = 1e-5;
+const MAX_STEPS: usize = 512;
+
+// Signed distance field of a user-defined shape, negative inside. The
+// distance may be underestimated but never overestimated, as marching steps
+// over anything closer than it.
+pub trait SignedDistance: Debug + Send + Sync {
+    fn distance(&self, p: Point3) -> f64;
+    // Box containing every point of negative distance
+    fn bounding_box(&self) -> Aabb;
+}
+
+#[derive(Debug, Clone)]
+pub enum Sdf {
+    Sphere {
+        center: Point3,
+        radius: f64,
+    },
+    Cuboid {
+        center: Point3,
+        half_size: Vec3,
+    },
+    // Tube of radius `minor` around a circle of radius `major` in the xz
+    // plane
+    Torus {
+        center: Point3,
+        major: f64,
+        minor: f64,
+    },
+    // Union blending both shapes where they are closer than `k`
+    SmoothUnion {
+        a: Arc<Sdf>,
+        b: Arc<Sdf>,
+        k: f64,
+    },
+    // Grid of copies spaced by `spacing`, `counts` along each axis. Exact as
+    // long as the shape fits in its cell.
+    Repeat {
+        sdf: Arc<Sdf>,
+        spacing: Vec3,
+        counts: [u32; 3],
+    },
+    Custom(Arc<dyn SignedDistance>),
+}
+
+impl Sdf {
+    pub fn sphere(center: Point3, radius: f64) -> Self {
+        Sdf::Sphere { center, radius }
+    }
+    pub fn cuboid(center: Point3, half_size: Vec3) -> Self {
+        Sdf::Cuboid { center, half_size }
+    }
+    pub fn torus(center: Point3, major: f64, minor: f64) -> Self {
+        Sdf::Torus {
+            center,
+            major,
+            minor,
+        }
+    }
+    pub fn smooth_union(a: Sdf, b: Sdf, k: f64) -> Self {
+        Sdf::SmoothUnion {
+            a: Arc::new(a),
+            b: Arc::new(b),
+            k,
+        }
+    }
+    pub fn repeat(sdf: Sdf, spacing: Vec3, counts: [u32; 3]) -> Self {
+        Sdf::Repeat {
+            sdf: Arc::new(sdf),
+            spacing,
+            counts,
+        }
+    }
+    pub fn custom(sdf: impl SignedDistance + 'static) -> Self {
+        Sdf::Custom(Arc::new(sdf))
+    }
+    pub fn distance(&self, p: Point3) -> f64 {
+        match self {
+            Sdf::Sphere { center, radius } => (p - *center).length() - radius,
+            Sdf::Cuboid { center, half_size } => {
+                let p = p - *center;
+                let q = Vec3::new(
+                    p.x.abs() - half_size.x,
+                    p.y.abs() - half_size.y,
+                    p.z.abs() - half_size.z,
+                );
+                let outside = Vec3::new(q.x.max(0.0), q.y.max(0.0), q.z.max(0.0)).length();
+                outside + q.x.max(q.y).max(q.z).min(0.0)
+            }
+            Sdf::Torus {
+                center,
+                major,
+                minor,
+            } => {
+                let p = p - *center;
+                let radial = (p.x * p.x + p.z * p.z).sqrt() - major;
+                (radial * radial + p.y * p.y).sqrt() - minor
+            }
+            Sdf::SmoothUnion { a, b, k } => {
+                let (a, b) = (a.distance(p), b.distance(p));
+                let h = (0.5 + 0.5 * (b - a) / k).clamp(0.0, 1.0);
+                b + h * (a - b) - k * h * (1.0 - h)
+            }
+            Sdf::Repeat {
+                sdf,
+                spacing,
+                counts,
+            } => {
+                // Fold the point into the nearest copy
+                let origin = sdf.bounding_box();
+                let offset = p - 0.5 * (origin.minimum + origin.maximum);
+                let mut index = [0.0; 3];
+                for (a, i) in index.iter_mut().enumerate() {
+                    if spacing[a as u64] != 0.0 {
+                        *i = (offset[a as u64] / spacing[a as u64])
+                            .round()
+                            .clamp(0.0, counts[a].max(1) as f64 - 1.0);
+                    }
+                }
+                sdf.distance(p - *spacing * Vec3::new(index[0], index[1], index[2]))
+            }
+            Sdf::Custom(c) => c.distance(p),
+        }
+    }
+    pub fn bounding_box(&self) -> Aabb {
+        match self {
+            Sdf::Sphere { center, radius } => {
+                let extent = *radius * Vec3::ones();
+                Aabb::new(*center - extent, *center + extent)
+            }
+            Sdf::Cuboid { center, half_size } => {
+                Aabb::new(*center - *half_size, *center + *half_size)
+            }
+            Sdf::Torus {
+                center,
+                major,
+                minor,
+            } => {
+                let extent = Vec3::new(major + minor, *minor, major + minor);
+                Aabb::new(*center - extent, *center + extent)
+            }
+            Sdf::SmoothUnion { a, b, k } => {
+                // The blend stays within k / 4 of the plain union
+                let union = Aabb::surronding_box(&a.bounding_box(), &b.bounding_box());
+                union.padded(0.25 * k)
+            }
+            Sdf::Repeat {
+                sdf,
+                spacing,
+                counts,
+            } => {
+                let first = sdf.bounding_box();
+                let shift = *spacing
+                    * Vec3::new(
+                        counts[0].max(1) as f64 - 1.0,
+                        counts[1].max(1) as f64 - 1.0,
+                        counts[2].max(1) as f64 - 1.0,
+                    );
+                Aabb::new(first.minimum, first.maximum + shift)
+            }
+            Sdf::Custom(c) => c.bounding_box(),
+        }
+    }
+    // Outward normal, as the gradient of the distance sampled on a
+    // tetrahedron around `p`
+    fn normal(&self, p: Point3) -> Vec3 {
+        [
+            Vec3::new(1., -1., -1.),
+            Vec3::new(-1., -1., 1.),
+            Vec3::new(-1., 1., -1.),
+            Vec3::new(1., 1., 1.),
+        ]
+        .into_iter()
+        .map(|k| self.distance(p + EPSILON * k) * k)
+        .fold(Vec3::zeros(), |sum, term| sum + term)
+        .normalize()
+    }
+}
+
+// Surface where a signed distance field is zero, found by sphere tracing.
+// It has no natural parametrization so uv are zero, textures relying on the
+// hit point instead.
+pub struct RayMarched {
+    sdf: Sdf,
+    bbox: Aabb,
+    material: Material,
+}
+
+impl RayMarched {
+    pub fn new(sdf: Sdf, material: Material) -> Self {
+        // Pad so the march starts off the surface of flat sides
+        let bbox = sdf.bounding_box();
+        RayMarched {
+            sdf,
+            bbox: bbox.padded(2.0 * EPSILON),
+            material,
+        }
+    }
+}
+
+impl Hittable for RayMarched {
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        let Some((mut t, t_end)) = self.bbox.clip(r, t_min, t_max) else {
+            return false;
+        };
+        let length = r.direction.length();
+
+        // March on the side of the surface the ray starts from, getting off
+        // the surface it may have just left first
+        let d = self.sdf.distance(r.at(t));
+        let mut side = d.signum();
+        if d.abs() < EPSILON {
+            side = if self.sdf.normal(r.at(t)).dot(r.direction) < 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+            while t <= t_end && side * self.sdf.distance(r.at(t)) < EPSILON {
+                t += EPSILON / length;
+            }
+        }
+
+        for _ in 0..MAX_STEPS {
+            if t > t_end {
+                return false;
+            }
+            let p = r.at(t);
+            let d = side * self.sdf.distance(p);
+            if d >= EPSILON {
+                t += d / length;
+                continue;
+            }
+            if self.material.is_opaque_at(r, t, 0.0, 0.0, p) {
+                let out_normal = self.sdf.normal(p);
+                let frame = Onb::from_w(out_normal);
+                rec.t = t;
+                rec.p = p;
+                (rec.u, rec.v) = (0.0, 0.0);
+                (rec.dpdu, rec.dpdv) = (frame.u, frame.v);
+                rec.set_face_normal(r, out_normal);
+                rec.material = &self.material;
+                return true;
+            }
+            // Go through the cutout and march on from the other side
+            side = -side;
+            while t <= t_end && side * self.sdf.distance(r.at(t)) < EPSILON {
+                t += EPSILON / length;
+            }
+        }
+        false
+    }
+
+    fn bounding_box(&self, _time_frame: (f64, f64)) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RayMarched, Sdf, SignedDistance};
+    use crate::{
+        raytracer::{
+            hittable::{aabb::Aabb, material::Material},
+            BvhNode, HitRecord, Hittable, HittableList, Ray,
+        },
+        vec3::{Point3, Vec3},
+    };
+    use rand::thread_rng;
+    use std::sync::Arc;
+
+    // Half-space below y = 0 cut to a slab, as a user-defined field
+    #[derive(Debug)]
+    struct Slab;
+
+    impl SignedDistance for Slab {
+        fn distance(&self, p: Point3) -> f64 {
+            p.y.max(-1.0 - p.y)
+        }
+        fn bounding_box(&self) -> Aabb {
+            Aabb::new(Point3::new(-1., -1., -1.), Point3::new(1., 0., 1.))
+        }
+    }
+
+    #[test]
+    fn distances() {
+        let p = Point3::new(3., 0., 0.);
+        assert_eq!(Sdf::sphere(Point3::zeros(), 1.).distance(p), 2.0);
+        let cuboid = Sdf::cuboid(Point3::zeros(), Vec3::new(1., 2., 3.));
+        assert_eq!(cuboid.distance(p), 2.0);
+        assert_eq!(cuboid.distance(Point3::new(2., 3., 0.)), 2f64.sqrt());
+        assert_eq!(cuboid.distance(Point3::zeros()), -1.0);
+        assert_eq!(Sdf::torus(Point3::zeros(), 2., 0.5).distance(p), 0.5);
+
+        // Copies at x = 0, 2 and 4, the last one closest to x = 5 and beyond
+        let row = Sdf::repeat(
+            Sdf::sphere(Point3::zeros(), 0.5),
+            Vec3::new(2., 0., 0.),
+            [3, 1, 1],
+        );
+        assert_eq!(row.distance(p), 0.5);
+        assert_eq!(row.distance(Point3::new(7., 0., 0.)), 2.5);
+        let bbox = row.bounding_box();
+        assert_eq!(bbox.maximum, Point3::new(4.5, 0.5, 0.5));
+
+        // Blending only ever adds matter
+        let a = Sdf::sphere(Point3::zeros(), 1.);
+        let b = Sdf::sphere(Point3::new(2.4, 0., 0.), 1.);
+        let blend = Sdf::smooth_union(a.clone(), b.clone(), 1.);
+        let mid = Point3::new(1.2, 0., 0.);
+        assert!(blend.distance(mid) < a.distance(mid).min(b.distance(mid)));
+        assert!(blend.distance(mid) < 0.0);
+    }
+
+    #[test]
+    fn hit() {
+        let sphere = RayMarched::new(Sdf::sphere(Point3::zeros(), 1.), Material::None);
+        let mut rec = HitRecord::new();
+
+        let ray = Ray::new(Point3::new(0., 0., 5.), Vec3::new(0., 0., -2.), 0.0);
+        assert!(sphere.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 2.0).abs() < 1e-4);
+        assert!((rec.normal - Vec3::new(0., 0., 1.)).length() < 1e-6);
+        assert!(rec.front_face);
+        assert!(rec.dpdu.dot(rec.normal).abs() < 1e-9);
+
+        // Leaving the surface it was hit on, the ray finds the far side
+        let ray = Ray::new(rec.p, Vec3::new(0., 0., -1.), 0.0);
+        assert!(sphere.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 2.0).abs() < 1e-4);
+        assert!(!rec.front_face);
+
+        let ray = Ray::new(Point3::new(0., 1.5, 5.), Vec3::new(0., 0., -1.), 0.0);
+        assert!(!sphere.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        let ray = Ray::new(Point3::new(0., 0., 5.), Vec3::new(0., 0., -1.), 0.0);
+        assert!(!sphere.hit(&ray, 0.001, 3.0, &mut rec));
+
+        let slab = RayMarched::new(Sdf::custom(Slab), Material::None);
+        let ray = Ray::new(Point3::new(0.5, 5., 0.), -Vec3::up(), 0.0);
+        assert!(slab.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 5.0).abs() < 1e-4);
+        assert!((rec.normal - Vec3::up()).length() < 1e-6);
+    }
+
+    #[test]
+    fn inside_bvh() {
+        let mut rng = thread_rng();
+        let mut objects = HittableList::new();
+        objects.add(Arc::new(RayMarched::new(
+            Sdf::repeat(
+                Sdf::torus(Point3::zeros(), 0.5, 0.2),
+                Vec3::new(2., 0., 2.),
+                [4, 1, 4],
+            ),
+            Material::None,
+        )));
+        objects.add(Arc::new(RayMarched::new(
+            Sdf::cuboid(Point3::new(0., 3., 0.), Vec3::ones()),
+            Material::None,
+        )));
+        let bvh = BvhNode::new(
+            &objects.objects,
+            &mut rng,
+            0,
+            objects.objects.len(),
+            &(0.0, 1.0),
+        );
+        let mut rec = HitRecord::new();
+
+        // Down onto the tube of the last torus of the grid
+        let ray = Ray::new(Point3::new(6.5, 5., 6.), -Vec3::up(), 0.0);
+        assert!(bvh.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 4.8).abs() < 1e-4);
+        // Onto the top of the box
+        let ray = Ray::new(Point3::new(0.5, 10., 0.5), -Vec3::up(), 0.0);
+        assert!(bvh.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 6.0).abs() < 1e-4);
+    }
+}
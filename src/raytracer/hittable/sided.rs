@@ -0,0 +1,142 @@
+use super::{aabb::Aabb, material, HitRecord, Hittable};
+use crate::raytracer::Ray;
+use std::sync::Arc;
+
+// What happens when an object is hit from behind, i.e. against its outward
+// normal. Objects are two-sided unless wrapped in `Sided`.
+#[derive(Debug, Copy, Clone)]
+enum Facing {
+    // The back side is there, but neither scatters nor emits light
+    OneSided,
+    // The back side is not there at all and rays go through it
+    Culled,
+}
+
+pub struct Sided {
+    object: Arc<dyn Hittable + Send + Sync>,
+    facing: Facing,
+}
+
+impl Sided {
+    pub fn one_sided(object: Arc<dyn Hittable + Send + Sync>) -> Self {
+        Sided {
+            object,
+            facing: Facing::OneSided,
+        }
+    }
+    pub fn culled(object: Arc<dyn Hittable + Send + Sync>) -> Self {
+        Sided {
+            object,
+            facing: Facing::Culled,
+        }
+    }
+}
+
+impl Hittable for Sided {
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        // `rec` may hold a closer hit of another object, and must be left
+        // alone unless something is found
+        let mut temp_rec = HitRecord::new();
+        let mut t_min = t_min;
+        loop {
+            if !self.object.hit(r, t_min, t_max, &mut temp_rec) {
+                return false;
+            }
+            if temp_rec.front_face {
+                break;
+            }
+            match self.facing {
+                Facing::OneSided => {
+                    temp_rec.material = &material::NONE;
+                    break;
+                }
+                // Look for the next front face behind this one
+                Facing::Culled => t_min = temp_rec.t.next_up(),
+            }
+        }
+        *rec = temp_rec;
+        true
+    }
+
+    fn bounding_box(&self, time_frame: (f64, f64)) -> Option<Aabb> {
+        self.object.bounding_box(time_frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sided;
+    use crate::{
+        raytracer::{
+            hittable::material::{DiffuseLight, Material},
+            AaRect, HitRecord, Hittable, HittableList, Ray, Sphere,
+        },
+        vec3::{Color, Point3, Vec3},
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn one_sided() {
+        let light = Arc::new(AaRect::xz(
+            -1.,
+            1.,
+            -1.,
+            1.,
+            0.,
+            DiffuseLight::new(Color::ones()),
+        ));
+        let panel = Sided::one_sided(light);
+        let mut rec = HitRecord::new();
+
+        // Seen from below, against the +y outward normal: still an occluder,
+        // but a black one
+        let from_below = Ray::new(Point3::new(0., -1., 0.), Vec3::up(), 0.0);
+        assert!(panel.hit(&from_below, 0.001, f64::INFINITY, &mut rec));
+        assert!(!rec.front_face);
+        assert!(matches!(rec.material, Material::None));
+
+        let from_above = Ray::new(Point3::new(0., 1., 0.), -Vec3::up(), 0.0);
+        assert!(panel.hit(&from_above, 0.001, f64::INFINITY, &mut rec));
+        assert!(rec.front_face);
+        assert_eq!(rec.material.emitted(&rec), Color::ones());
+    }
+
+    #[test]
+    fn culled() {
+        let sphere = Arc::new(Sphere::new(Point3::zeros(), 1., Material::None));
+        let culled = Sided::culled(sphere);
+        let mut scene = HittableList::new();
+        scene.add(Arc::new(AaRect::xy(-1., 1., -1., 1., 0., Material::None)));
+        scene.add(Arc::new(Sphere::new(
+            Point3::new(0., 0., 5.),
+            1.,
+            Material::None,
+        )));
+        let scene = Arc::new(scene);
+        let culled_scene = Sided::culled(scene.clone());
+        let mut rec = HitRecord::new();
+
+        // From outside the front face is found as usual
+        let outside = Ray::new(Point3::new(0., 0., 5.), Vec3::new(0., 0., -1.), 0.0);
+        assert!(culled.hit(&outside, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!(rec.t, 4.0);
+        assert!(rec.front_face);
+
+        // From inside only the back of the sphere is in the way
+        let inside = Ray::new(Point3::zeros(), Vec3::new(0., 0., -1.), 0.0);
+        assert!(!culled.hit(&inside, 0.001, f64::INFINITY, &mut rec));
+
+        // Behind a culled back face, the next front face is still hit
+        let ray = Ray::new(Point3::new(0., 0., -1.), Vec3::new(0., 0., 1.), 0.0);
+        assert!(scene.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!(rec.t, 1.0);
+        assert!(culled_scene.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!(rec.t, 5.0);
+        assert!(rec.front_face);
+
+        // Missing leaves the previous closest hit untouched
+        rec.t = 42.0;
+        assert!(!culled.hit(&inside, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!(rec.t, 42.0);
+    }
+}
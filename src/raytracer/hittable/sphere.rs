@@ -1,44 +1,75 @@
-use super::{aabb::Aabb, material::Material, HitRecord, Hittable};
+mod motion;
+
+use super::{keyframes, light::Light, material::Material, HitRecord, Hittable};
 use crate::{
     raytracer::Ray,
-    vec3::{Point3, Vec3},
+    vec3::{Onb, Point3, Vec3},
 };
+pub use motion::MotionPath;
+use rand::{Rng, RngCore};
+use std::f64::consts::PI;
 
 pub struct Sphere {
-    center: Point3,
-    speed: Vec3,
-    radius: f64,
+    path: MotionPath,
+    // Radius keyframes, sorted by time
+    radii: Vec<(f64, f64)>,
     material: Material,
 }
 
 impl Sphere {
     pub fn new(center: Point3, radius: f64, material: Material) -> Self {
-        Sphere {
-            center,
-            speed: Vec3::zeros(),
-            radius,
-            material,
-        }
+        Self::along(MotionPath::fixed(center), radius, material)
     }
     pub fn new_moving(center: Point3, speed: Vec3, radius: f64, material: Material) -> Self {
+        Self::along(MotionPath::linear(center, speed), radius, material)
+    }
+    pub fn along(path: MotionPath, radius: f64, material: Material) -> Self {
         Sphere {
-            center,
-            speed,
-            radius,
+            path,
+            radii: vec![(0.0, radius)],
             material,
         }
     }
+    // Radius growing or shrinking linearly between keyframes, instead of
+    // staying the same
+    pub fn with_radii(mut self, keyframes: Vec<(f64, f64)>) -> Self {
+        self.radii = keyframes::sorted(keyframes);
+        self
+    }
     pub fn center(&self, time: f64) -> Point3 {
-        self.center + time * self.speed
+        self.path.at(time)
+    }
+    pub fn radius(&self, time: f64) -> f64 {
+        keyframes::interpolate(&self.radii, time)
+    }
+    // Longitude and latitude of a point on the unit sphere, in [0, 1]
+    fn uv(p: Point3) -> (f64, f64) {
+        let theta = (-p.y).acos();
+        let phi = (-p.z).atan2(p.x) + PI;
+        (phi / (2.0 * PI), theta / PI)
+    }
+    // Derivatives of the point at `normal` along the coordinates of `uv`
+    fn dpduv(radius: f64, normal: Vec3) -> (Vec3, Vec3) {
+        let sin_theta = (1.0 - normal.y * normal.y).sqrt().max(1e-8);
+        let dpdu = 2.0 * PI * radius * Vec3::new(normal.z, 0.0, -normal.x);
+        let dpdv = PI
+            * radius
+            * Vec3::new(
+                -normal.y * normal.x / sin_theta,
+                sin_theta,
+                -normal.y * normal.z / sin_theta,
+            );
+        (dpdu, dpdv)
     }
 }
 
 impl Hittable for Sphere {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
-        let oc = r.origin - self.center(r.time);
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        let (center, radius) = (self.center(r.time), self.radius(r.time));
+        let oc = r.origin - center;
         let a = r.direction.length_squared();
         let half_b = oc.dot(r.direction);
-        let c = oc.length_squared() - self.radius * self.radius;
+        let c = oc.length_squared() - radius * radius;
 
         let discriminant = half_b * half_b - a * c;
         if discriminant < 0.0 {
@@ -46,48 +77,111 @@ impl Hittable for Sphere {
         }
         let sqrtd = discriminant.sqrt();
 
-        let mut root = (-half_b - sqrtd) / a;
-        if root < t_min || t_max < root {
-            root = (-half_b + sqrtd) / a;
+        // Nearest root first, the far one showing through cutout holes
+        for root in [(-half_b - sqrtd) / a, (-half_b + sqrtd) / a] {
             if root < t_min || t_max < root {
-                return false;
+                continue;
             }
+            let p = r.at(root);
+            let out_normal = (p - center) / radius;
+            let (u, v) = Self::uv(out_normal);
+            if !self.material.is_opaque_at(r, root, u, v, p) {
+                continue;
+            }
+            rec.t = root;
+            rec.p = p;
+            rec.set_face_normal(r, out_normal);
+            (rec.u, rec.v) = (u, v);
+            (rec.dpdu, rec.dpdv) = Self::dpduv(radius, out_normal);
+            rec.material = &self.material;
+            return true;
         }
-        rec.t = root;
-        rec.p = r.at(rec.t);
-        let out_normal = (rec.p - self.center(r.time)) / self.radius;
-        rec.set_face_normal(r, out_normal);
-        rec.material = self.material;
-
-        true
+        false
     }
 
     fn bounding_box(&self, time_frame: (f64, f64)) -> Option<super::Aabb> {
-        let box_a = Aabb::new(
-            self.center(time_frame.0) - Vec3::new(self.radius, self.radius, self.radius),
-            self.center(time_frame.0) + Vec3::new(self.radius, self.radius, self.radius),
-        );
+        let centers = self.path.bounding_box(time_frame);
+        // The radius is largest at either end of the time frame or at one
+        // of its keyframes
+        let (start, end) = time_frame;
+        let radius = self
+            .radii
+            .iter()
+            .filter(|&&(t, _)| start < t && t < end)
+            .fold(self.radius(start).max(self.radius(end)), |max, &(_, r)| {
+                max.max(r)
+            });
+        Option::Some(centers.padded(radius))
+    }
+}
 
-        let box_b = Aabb::new(
-            self.center(time_frame.1) - Vec3::new(self.radius, self.radius, self.radius),
-            self.center(time_frame.1) + Vec3::new(self.radius, self.radius, self.radius),
-        );
+impl Light for Sphere {
+    fn pdf_value(&self, r: &Ray) -> f64 {
+        let mut rec = HitRecord::new();
+        if !self.hit(r, 0.001, f64::INFINITY, &mut rec) {
+            return 0.0;
+        }
+        // Uniform density over the cone subtended by the sphere
+        let radius = self.radius(r.time);
+        let distance_squared = (self.center(r.time) - r.origin).length_squared();
+        let cos_theta_max = (1.0 - radius * radius / distance_squared).max(0.0).sqrt();
+        1.0 / (2.0 * PI * (1.0 - cos_theta_max))
+    }
 
-        Option::Some(Aabb::surronding_box(&box_a, &box_b))
+    fn random(&self, origin: Point3, time: f64, rng: &mut dyn RngCore) -> Vec3 {
+        let radius = self.radius(time);
+        let direction = self.center(time) - origin;
+        let distance_squared = direction.length_squared();
+        let cos_theta_max = (1.0 - radius * radius / distance_squared).max(0.0).sqrt();
+
+        let r1: f64 = rng.gen_range(0.0..1.0);
+        let r2: f64 = rng.gen_range(0.0..1.0);
+        let z = 1.0 + r2 * (cos_theta_max - 1.0);
+        let phi = 2.0 * PI * r1;
+        let sin_theta = (1.0 - z * z).sqrt();
+
+        Onb::from_w(direction).local(Vec3::new(phi.cos() * sin_theta, phi.sin() * sin_theta, z))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Sphere;
+    use super::{MotionPath, Sphere};
     use crate::{
         raytracer::{
             hittable::{aabb::Aabb, material::Material},
-            Hittable,
+            HitRecord, Hittable, Ray,
         },
         vec3::Vec3,
     };
 
+    #[test]
+    fn uv() {
+        let (u, v) = Sphere::uv(Vec3::new(1., 0., 0.));
+        assert_eq!((u, v), (0.5, 0.5));
+        let (_, v) = Sphere::uv(Vec3::up());
+        assert_eq!(v, 1.0);
+        let (u, v) = Sphere::uv(Vec3::new(0., 0., 1.));
+        assert_eq!((u, v), (0.25, 0.5));
+    }
+
+    #[test]
+    fn dpduv() {
+        // Moving along dpdu and dpdv moves the uv coordinates by as much
+        let sphere = Sphere::new(Vec3::zeros(), 2.0, Material::None);
+        let eps = 1e-6;
+        for normal in [Vec3::new(0., 0., 1.), Vec3::new(0.3, -0.5, 0.6).normalize()] {
+            let (u, v) = Sphere::uv(normal);
+            let (dpdu, dpdv) = Sphere::dpduv(sphere.radius(0.0), normal);
+            let (u_du, v_du) = Sphere::uv((2.0 * normal + eps * dpdu).normalize());
+            let (u_dv, v_dv) = Sphere::uv((2.0 * normal + eps * dpdv).normalize());
+            assert!(((u_du - u) / eps - 1.0).abs() < 1e-4);
+            assert!(((v_du - v) / eps).abs() < 1e-4);
+            assert!(((u_dv - u) / eps).abs() < 1e-4);
+            assert!(((v_dv - v) / eps - 1.0).abs() < 1e-4);
+        }
+    }
+
     #[test]
     fn bounding_box() {
         let sphere = Sphere::new(Vec3::zeros(), 1.0, Material::None);
@@ -109,4 +203,32 @@ mod tests {
             sphere.bounding_box((0.0, 1.0)).unwrap()
         );
     }
+    #[test]
+    fn bounding_box_animated() {
+        // Rising past a keyframe while swelling then shrinking back
+        let sphere = Sphere::along(
+            MotionPath::keyframes(vec![
+                (0.0, Vec3::zeros()),
+                (0.5, Vec3::new(1., 1., 0.)),
+                (1.0, Vec3::new(2., 0., 0.)),
+            ]),
+            1.0,
+            Material::None,
+        )
+        .with_radii(vec![(0.0, 1.0), (0.5, 2.0), (1.0, 1.0)]);
+        assert_eq!(sphere.radius(0.25), 1.5);
+        assert_eq!(
+            sphere.bounding_box((0.0, 1.0)).unwrap(),
+            Aabb::new(Vec3::new(-2., -2., -2.), Vec3::new(4., 3., 2.))
+        );
+        assert_eq!(
+            sphere.bounding_box((0.0, 0.25)).unwrap(),
+            Aabb::new(Vec3::new(-1.5, -1.5, -1.5), Vec3::new(2., 2., 1.5))
+        );
+
+        let mut rec = HitRecord::new();
+        let ray = Ray::new(Vec3::new(1., 5., 0.), -Vec3::up(), 0.5);
+        assert!(sphere.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert_eq!(rec.t, 2.0);
+    }
 }
@@ -0,0 +1,180 @@
+use crate::{
+    raytracer::hittable::{aabb::Aabb, bezier::bezier, keyframes},
+    vec3::{Point3, Vec3},
+};
+
+// Where the center of a sphere is over time
+#[derive(Debug, Clone)]
+pub enum MotionPath {
+    // At `start` at time 0, moving by `speed` per unit time
+    Linear {
+        start: Point3,
+        speed: Vec3,
+    },
+    // Through each point at its time, in straight lines in between, and still
+    // before the first and after the last
+    Keyframes(Vec<(f64, Point3)>),
+    // Along a cubic Bézier curve, from its first control point at the start of
+    // `time_frame` to its last at the end, and still outside of it
+    Bezier {
+        points: [Point3; 4],
+        time_frame: (f64, f64),
+    },
+}
+
+impl MotionPath {
+    pub fn fixed(center: Point3) -> Self {
+        Self::linear(center, Vec3::zeros())
+    }
+    pub fn linear(start: Point3, speed: Vec3) -> Self {
+        MotionPath::Linear { start, speed }
+    }
+    pub fn keyframes(keyframes: Vec<(f64, Point3)>) -> Self {
+        MotionPath::Keyframes(keyframes::sorted(keyframes))
+    }
+    pub fn bezier(points: [Point3; 4], time_frame: (f64, f64)) -> Self {
+        assert!(
+            time_frame.0 < time_frame.1,
+            "A Bézier path needs a time frame"
+        );
+        MotionPath::Bezier { points, time_frame }
+    }
+
+    pub fn at(&self, time: f64) -> Point3 {
+        match self {
+            MotionPath::Linear { start, speed } => *start + time * *speed,
+            MotionPath::Keyframes(keyframes) => keyframes::interpolate(keyframes, time),
+            MotionPath::Bezier { points, time_frame } => {
+                bezier(points, Self::parameter(*time_frame, time))
+            }
+        }
+    }
+    // Box around every position over `time_frame`, touching the path
+    pub fn bounding_box(&self, time_frame: (f64, f64)) -> Aabb {
+        let (start, end) = time_frame;
+        let point_box = |p: Point3| Aabb::new(p, p);
+        let ends = Aabb::surronding_box(&point_box(self.at(start)), &point_box(self.at(end)));
+        match self {
+            // Straight lines between where the path turns
+            MotionPath::Linear { .. } => ends,
+            MotionPath::Keyframes(keyframes) => keyframes
+                .iter()
+                .filter(|&&(t, _)| start < t && t < end)
+                .fold(ends, |bbox, &(_, p)| {
+                    Aabb::surronding_box(&bbox, &point_box(p))
+                }),
+            // The curve also reaches out where its derivative along an axis
+            // vanishes: roots of 3 [(1-s)² a + 2s(1-s) b + s² c] with a, b
+            // and c the differences between its control points
+            MotionPath::Bezier { points, time_frame } => {
+                let (s0, s1) = (
+                    Self::parameter(*time_frame, start),
+                    Self::parameter(*time_frame, end),
+                );
+                let [a, b, c] = [
+                    points[1] - points[0],
+                    points[2] - points[1],
+                    points[3] - points[2],
+                ];
+                let mut bbox = ends;
+                for axis in 0..3 {
+                    let (a, b, c) = (a[axis], b[axis], c[axis]);
+                    for s in quadratic_roots(a - 2.0 * b + c, 2.0 * (b - a), a) {
+                        if s0 < s && s < s1 {
+                            bbox = Aabb::surronding_box(&bbox, &point_box(bezier(points, s)));
+                        }
+                    }
+                }
+                bbox
+            }
+        }
+    }
+    // Curve parameter of a Bézier path at `time`
+    fn parameter(time_frame: (f64, f64), time: f64) -> f64 {
+        ((time - time_frame.0) / (time_frame.1 - time_frame.0)).clamp(0.0, 1.0)
+    }
+}
+
+// Real roots of a s² + b s + c
+fn quadratic_roots(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a.abs() < 1e-12 {
+        return if b == 0.0 { vec![] } else { vec![-c / b] };
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return vec![];
+    }
+    let sqrtd = discriminant.sqrt();
+    vec![(-b - sqrtd) / (2.0 * a), (-b + sqrtd) / (2.0 * a)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MotionPath;
+    use crate::vec3::{Point3, Vec3};
+
+    #[test]
+    fn positions() {
+        let linear = MotionPath::linear(Point3::zeros(), Vec3::up());
+        assert_eq!(linear.at(2.0), Point3::new(0., 2., 0.));
+
+        let keyframes = MotionPath::keyframes(vec![
+            (1.0, Point3::new(2., 2., 0.)),
+            (0.0, Point3::zeros()),
+            (2.0, Point3::new(4., 0., 0.)),
+        ]);
+        assert_eq!(keyframes.at(-1.0), Point3::zeros());
+        assert_eq!(keyframes.at(0.5), Point3::new(1., 1., 0.));
+        assert_eq!(keyframes.at(1.5), Point3::new(3., 1., 0.));
+        assert_eq!(keyframes.at(3.0), Point3::new(4., 0., 0.));
+
+        let points = [
+            Point3::zeros(),
+            Point3::new(0., 2., 0.),
+            Point3::new(2., 2., 0.),
+            Point3::new(2., 0., 0.),
+        ];
+        let hop = MotionPath::bezier(points, (1.0, 3.0));
+        assert_eq!(hop.at(0.0), points[0]);
+        assert_eq!(hop.at(2.0), Point3::new(1., 1.5, 0.));
+        assert_eq!(hop.at(4.0), points[3]);
+    }
+
+    #[test]
+    fn bounding_box() {
+        // The top of a hop lies beyond both of its ends
+        let hop = MotionPath::bezier(
+            [
+                Point3::zeros(),
+                Point3::new(0., 2., 1.),
+                Point3::new(2., 2., -1.),
+                Point3::new(2., 0., 0.),
+            ],
+            (0.0, 1.0),
+        );
+        let bbox = hop.bounding_box((0.0, 1.0));
+        let samples: Vec<Point3> = (0..=1000).map(|i| hop.at(i as f64 / 1000.0)).collect();
+        for a in 0..3 {
+            let min = samples.iter().map(|p| p[a]).fold(f64::INFINITY, f64::min);
+            let max = samples
+                .iter()
+                .map(|p| p[a])
+                .fold(f64::NEG_INFINITY, f64::max);
+            assert!(bbox.minimum[a] <= min && min - bbox.minimum[a] < 1e-5);
+            assert!(bbox.maximum[a] >= max && bbox.maximum[a] - max < 1e-5);
+        }
+        assert!((bbox.maximum.y - 1.5).abs() < 1e-12);
+        // Over the first half only, the hop is still rising
+        let rising = hop.bounding_box((0.0, 0.5));
+        assert_eq!(rising.maximum.y, 1.5);
+        assert_eq!(rising.maximum.x, 1.0);
+
+        let keyframes = MotionPath::keyframes(vec![
+            (0.0, Point3::zeros()),
+            (1.0, Point3::new(1., 3., 0.)),
+            (2.0, Point3::new(2., 0., 0.)),
+        ]);
+        assert_eq!(keyframes.bounding_box((0.0, 2.0)).maximum.y, 3.0);
+        assert_eq!(keyframes.bounding_box((0.0, 0.5)).maximum.y, 1.5);
+    }
+}
@@ -0,0 +1,84 @@
+pub mod noise;
+
+use crate::vec3::{Color, Point3};
+
+#[derive(Debug, Copy, Clone)]
+pub enum Texture {
+    Solid(Color),
+    // 3D checker pattern, `scale` being the size of a cell
+    Checker { even: Color, odd: Color, scale: f64 },
+    // Gray turbulent Perlin noise, `scale` being its frequency
+    Noise { scale: f64 },
+}
+
+impl Texture {
+    pub fn checker(even: Color, odd: Color, scale: f64) -> Self {
+        Texture::Checker { even, odd, scale }
+    }
+    pub fn noise(scale: f64) -> Self {
+        Texture::Noise { scale }
+    }
+    pub fn value(&self, _u: f64, _v: f64, p: Point3) -> Color {
+        match *self {
+            Texture::Solid(color) => color,
+            Texture::Checker { even, odd, scale } => {
+                let cell = (p.x / scale).floor() + (p.y / scale).floor() + (p.z / scale).floor();
+                if cell.rem_euclid(2.0) == 0.0 {
+                    even
+                } else {
+                    odd
+                }
+            }
+            Texture::Noise { scale } => noise::turbulence(scale * p, 7).min(1.0) * Color::ones(),
+        }
+    }
+    // Scalar textures (roughness, metallic...) read the first channel
+    pub fn scalar(&self, u: f64, v: f64, p: Point3) -> f64 {
+        self.value(u, v, p).x
+    }
+}
+
+impl From<Color> for Texture {
+    fn from(color: Color) -> Self {
+        Texture::Solid(color)
+    }
+}
+
+impl From<f64> for Texture {
+    fn from(value: f64) -> Self {
+        Texture::Solid(Color::new(value, value, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Texture;
+    use crate::vec3::{Color, Point3};
+
+    #[test]
+    fn checker() {
+        let texture = Texture::checker(Color::ones(), Color::zeros(), 1.0);
+        assert_eq!(
+            texture.value(0., 0., Point3::new(0.5, 0.5, 0.5)),
+            Color::ones()
+        );
+        assert_eq!(
+            texture.value(0., 0., Point3::new(1.5, 0.5, 0.5)),
+            Color::zeros()
+        );
+        assert_eq!(
+            texture.value(0., 0., Point3::new(-0.5, 0.5, 0.5)),
+            Color::zeros()
+        );
+        assert_eq!(
+            texture.value(0., 0., Point3::new(-0.5, -0.5, 0.5)),
+            Color::ones()
+        );
+    }
+
+    #[test]
+    fn scalar() {
+        let texture = Texture::from(0.25);
+        assert_eq!(texture.scalar(0.3, 0.7, Point3::zeros()), 0.25);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::vec3::Point3;
+
+// Scrambles lattice coordinates, replacing the permutation table of Perlin's
+// improved noise so textures stay small and `Copy`
+fn hash(x: i64, y: i64, z: i64) -> u32 {
+    let mut h = (x as u32).wrapping_mul(73_856_093)
+        ^ (y as u32).wrapping_mul(19_349_663)
+        ^ (z as u32).wrapping_mul(83_492_791);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5bd1_e995);
+    h ^ (h >> 15)
+}
+
+// Dot product with one of the 12 gradients pointing to the edges of a cube
+fn gradient(hash: u32, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = match h {
+        0..=3 => y,
+        12 | 14 => x,
+        _ => z,
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+// Perlin gradient noise, in [-1, 1] and zero on the integer lattice
+pub fn noise(p: Point3) -> f64 {
+    let (xf, yf, zf) = (p.x.floor(), p.y.floor(), p.z.floor());
+    let (xi, yi, zi) = (xf as i64, yf as i64, zf as i64);
+    let (x, y, z) = (p.x - xf, p.y - yf, p.z - zf);
+    let (u, v, w) = (fade(x), fade(y), fade(z));
+
+    let corner = |dx: i64, dy: i64, dz: i64| {
+        gradient(
+            hash(xi + dx, yi + dy, zi + dz),
+            x - dx as f64,
+            y - dy as f64,
+            z - dz as f64,
+        )
+    };
+    lerp(
+        w,
+        lerp(
+            v,
+            lerp(u, corner(0, 0, 0), corner(1, 0, 0)),
+            lerp(u, corner(0, 1, 0), corner(1, 1, 0)),
+        ),
+        lerp(
+            v,
+            lerp(u, corner(0, 0, 1), corner(1, 0, 1)),
+            lerp(u, corner(0, 1, 1), corner(1, 1, 1)),
+        ),
+    )
+}
+
+// Sum of `depth` octaves of noise, each twice as fine and half as strong
+pub fn turbulence(p: Point3, depth: u32) -> f64 {
+    let mut sum = 0.0;
+    let mut point = p;
+    let mut weight = 1.0;
+    for _ in 0..depth {
+        sum += weight * noise(point).abs();
+        weight *= 0.5;
+        point *= 2.0;
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{noise, turbulence};
+    use crate::vec3::Point3;
+
+    #[test]
+    fn noise_range() {
+        assert_eq!(noise(Point3::new(3., -2., 7.)), 0.0);
+        let mut sum = 0.0;
+        for i in 0..1000 {
+            let p = Point3::new(i as f64 * 0.37, i as f64 * 0.11 - 20., i as f64 * 0.53);
+            let n = noise(p);
+            assert!((-1.0..=1.0).contains(&n));
+            assert!(turbulence(p, 7) >= 0.0);
+            sum += n;
+        }
+        // Unbiased, but not constant
+        assert!((sum / 1000.0).abs() < 0.1);
+        assert_ne!(
+            noise(Point3::new(0.3, 0.6, 0.2)),
+            noise(Point3::new(1.3, 0.6, 0.2))
+        );
+    }
+}
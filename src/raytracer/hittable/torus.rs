@@ -0,0 +1,243 @@
+use super::{aabb::Aabb, disk::circle_extent, material::Material, HitRecord, Hittable};
+use crate::{
+    raytracer::Ray,
+    vec3::{Onb, Point3, Vec3},
+};
+use std::f64::consts::PI;
+
+// Torus around `center` whose tube of radius `minor` follows a circle of
+// radius `major` facing `axis`. u goes around the axis and v around the tube.
+pub struct Torus {
+    center: Point3,
+    frame: Onb,
+    major: f64,
+    minor: f64,
+    material: Material,
+}
+
+impl Torus {
+    pub fn new(center: Point3, axis: Vec3, major: f64, minor: f64, material: Material) -> Self {
+        Torus {
+            center,
+            frame: Onb::from_w(axis),
+            major,
+            minor,
+            material,
+        }
+    }
+}
+
+impl Hittable for Torus {
+    fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        // Solve from the point of the ray closest to the center along a unit
+        // direction, which keeps the quartic's coefficients small
+        let length = r.direction.length();
+        let direction = self.frame.to_local(r.direction / length);
+        let origin = self.frame.to_local(r.origin - self.center);
+        let t_closest = -origin.dot(direction);
+        let origin = origin + t_closest * direction;
+        let e = origin.length_squared();
+        if e > (self.major + self.minor).powi(2) {
+            return false;
+        }
+
+        // (|p|² + R² - r²)² = 4R² (x² + y²), with |p|² = s² + e
+        let major2 = self.major * self.major;
+        let k = e + major2 - self.minor * self.minor;
+        let p = 2.0 * k - 4.0 * major2 * (direction.x * direction.x + direction.y * direction.y);
+        let q = -8.0 * major2 * (origin.x * direction.x + origin.y * direction.y);
+        let r2 = k * k - 4.0 * major2 * (origin.x * origin.x + origin.y * origin.y);
+        let mut roots = solve_depressed_quartic(p, q, r2);
+        roots.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        // Nearest root first, the far ones showing through cutout holes
+        for s in roots {
+            let t = (t_closest + s) / length;
+            if t < t_min || t_max < t {
+                continue;
+            }
+            let local = origin + s * direction;
+            let phi = local.y.atan2(local.x).rem_euclid(2.0 * PI);
+            let radial = Vec3::new(phi.cos(), phi.sin(), 0.0);
+            let tube = local - self.major * radial;
+            let theta = tube.z.atan2(tube.dot(radial)).rem_euclid(2.0 * PI);
+            let (u, v) = (phi / (2.0 * PI), theta / (2.0 * PI));
+            let p = r.at(t);
+            if !self.material.is_opaque_at(r, t, u, v, p) {
+                continue;
+            }
+            let out_normal = self.frame.local(tube).normalize();
+            rec.t = t;
+            rec.p = p;
+            (rec.u, rec.v) = (u, v);
+            rec.dpdu = self
+                .frame
+                .local(2.0 * PI * Vec3::new(-local.y, local.x, 0.0));
+            rec.dpdv = self.frame.local(
+                2.0 * PI
+                    * self.minor
+                    * (theta.cos() * Vec3::new(0.0, 0.0, 1.0) - theta.sin() * radial),
+            );
+            rec.set_face_normal(r, out_normal);
+            rec.material = &self.material;
+            return true;
+        }
+        false
+    }
+
+    fn bounding_box(&self, _time_frame: (f64, f64)) -> Option<Aabb> {
+        let extent = circle_extent(self.frame.w, self.major) + self.minor * Vec3::ones();
+        Some(Aabb::new(self.center - extent, self.center + extent))
+    }
+}
+
+// Real roots of y⁴ + p y² + q y + r, by Ferrari's method
+fn solve_depressed_quartic(p: f64, q: f64, r: f64) -> Vec<f64> {
+    let mut roots = Vec::with_capacity(4);
+    if q.abs() < 1e-12 {
+        // Biquadratic, a quadratic in y²
+        for z in solve_quadratic(1.0, p, r) {
+            if z >= 0.0 {
+                roots.extend([-z.sqrt(), z.sqrt()]);
+            }
+        }
+    } else {
+        // y⁴ + p y² + q y + r = (y² + p / 2 + m)² - (s y - q / 2s)² once m
+        // solves the resolvent cubic, s being sqrt(2m)
+        let m = largest_cubic_root(p, p * p / 4.0 - r, -q * q / 8.0);
+        let s = (2.0 * m).max(0.0).sqrt();
+        if s == 0.0 {
+            return roots;
+        }
+        roots.extend(solve_quadratic(1.0, -s, p / 2.0 + m + q / (2.0 * s)));
+        roots.extend(solve_quadratic(1.0, s, p / 2.0 + m - q / (2.0 * s)));
+    }
+    // Polish away the precision lost along the way
+    for y in roots.iter_mut() {
+        for _ in 0..2 {
+            let f = ((*y * *y + p) * *y + q) * *y + r;
+            let df = (4.0 * *y * *y + 2.0 * p) * *y + q;
+            if df != 0.0 {
+                *y -= f / df;
+            }
+        }
+    }
+    roots
+}
+
+fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return Vec::new();
+    }
+    // Avoids the cancellation of -b + sqrt(discriminant)
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    if q == 0.0 {
+        return vec![0.0, 0.0];
+    }
+    vec![q / a, c / q]
+}
+
+// Largest real root of m³ + a m² + b m + c
+fn largest_cubic_root(a: f64, b: f64, c: f64) -> f64 {
+    // Depressed as t³ + p t + q with m = t - a / 3
+    let p = b - a * a / 3.0;
+    let q = 2.0 * a * a * a / 27.0 - a * b / 3.0 + c;
+    let discriminant = q * q / 4.0 + p * p * p / 27.0;
+    let t = if discriminant > 0.0 {
+        let sqrtd = discriminant.sqrt();
+        (-q / 2.0 + sqrtd).cbrt() + (-q / 2.0 - sqrtd).cbrt()
+    } else {
+        // Three real roots, the first of the trigonometric ones the largest
+        let rho = (-p / 3.0).sqrt();
+        let cos = if rho > 0.0 {
+            (-q / (2.0 * rho * rho * rho)).clamp(-1.0, 1.0)
+        } else {
+            1.0
+        };
+        2.0 * rho * (cos.acos() / 3.0).cos()
+    };
+    let mut m = t - a / 3.0;
+    let f = ((m + a) * m + b) * m + c;
+    let df = (3.0 * m + 2.0 * a) * m + b;
+    if df != 0.0 {
+        m -= f / df;
+    }
+    m
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve_depressed_quartic, Torus};
+    use crate::{
+        raytracer::{hittable::material::Material, HitRecord, Hittable, Ray},
+        vec3::{Point3, Vec3},
+    };
+
+    #[test]
+    fn quartic() {
+        // (y - 1)(y + 2)(y - 3)(y + 2) = y⁴ - 9 y² - 4 y + 12
+        let mut roots = solve_depressed_quartic(-9.0, -4.0, 12.0);
+        roots.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let expected = [-2.0, -2.0, 1.0, 3.0];
+        assert_eq!(roots.len(), 4);
+        for (root, expected) in roots.iter().zip(expected) {
+            assert!((root - expected).abs() < 1e-6, "{:?}", roots);
+        }
+        // (y² - 4)(y² - 1)
+        let mut roots = solve_depressed_quartic(-5.0, 0.0, 4.0);
+        roots.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(roots, vec![-2.0, -1.0, 1.0, 2.0]);
+        // No real roots
+        assert!(solve_depressed_quartic(1.0, 0.5, 3.0).is_empty());
+    }
+
+    #[test]
+    fn hit() {
+        let torus = Torus::new(Point3::zeros(), Vec3::up(), 2., 0.5, Material::None);
+        let mut rec = HitRecord::new();
+
+        // Through the tube on both sides, and through the hole
+        let ray = Ray::new(Point3::new(-5., 0., 0.), Vec3::new(2., 0., 0.), 0.0);
+        assert!(torus.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 1.25).abs() < 1e-9);
+        assert!((rec.normal - Vec3::new(-1., 0., 0.)).length() < 1e-9);
+        assert!(torus.hit(&ray, 1.3, f64::INFINITY, &mut rec));
+        assert!((rec.t - 1.75).abs() < 1e-9);
+        assert!(!rec.front_face);
+        assert!(torus.hit(&ray, 1.8, f64::INFINITY, &mut rec));
+        assert!((rec.t - 3.25).abs() < 1e-9);
+        let ray = Ray::new(Point3::new(0., 5., 0.), -Vec3::up(), 0.0);
+        assert!(!torus.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+
+        // Down onto the top of the tube
+        let ray = Ray::new(Point3::new(0., 5., 2.), -Vec3::up(), 0.0);
+        assert!(torus.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.t - 4.5).abs() < 1e-9);
+        assert!((rec.normal - Vec3::up()).length() < 1e-9);
+        assert!(rec.dpdu.dot(rec.normal).abs() < 1e-9);
+        assert!(rec.dpdv.dot(rec.normal).abs() < 1e-9);
+    }
+
+    #[test]
+    fn far_away_hits() {
+        // Precision holds for rays starting far from the torus
+        let torus = Torus::new(
+            Point3::new(1., 2., 3.),
+            Vec3::new(1., 1., 0.),
+            1.,
+            0.25,
+            Material::None,
+        );
+        let mut rec = HitRecord::new();
+        let target = Point3::new(1., 2., 3.) + Vec3::new(0., 0., 1.);
+        let origin = Point3::new(1e4, -3e3, 2e4);
+        let ray = Ray::new(origin, target - origin, 0.0);
+        assert!(torus.hit(&ray, 0.001, f64::INFINITY, &mut rec));
+        let p = rec.p - Point3::new(1., 2., 3.);
+        let axis = Vec3::new(1., 1., 0.).normalize();
+        let height = p.dot(axis);
+        let distance = (p - height * axis).length();
+        assert!(((distance - 1.0).powi(2) + height * height - 0.0625).abs() < 1e-6);
+    }
+}
@@ -1,12 +1,17 @@
-use crate::raytracer::{HitRecord, Hittable};
+use crate::raytracer::{HitRecord, Light, Scene};
 use crate::vec3::{Color, Point3, Vec3};
-use rand::rngs::ThreadRng;
+use rand::{Rng, RngCore};
+
+// Bounces always traced before Russian roulette may terminate a path
+const RR_MIN_BOUNCES: u16 = 3;
 
 #[derive(Debug)]
 pub struct Ray {
     pub origin: Point3,
     pub direction: Vec3,
     pub time: f64,
+    // Wavelength in nanometers carried by the path in spectral mode
+    pub wavelength: Option<f64>,
 }
 
 impl Ray {
@@ -15,29 +20,256 @@ impl Ray {
             origin,
             direction,
             time,
+            wavelength: None,
         }
     }
+    pub fn with_wavelength(mut self, wavelength: f64) -> Self {
+        self.wavelength = Some(wavelength);
+        self
+    }
     pub fn at(&self, t: f64) -> Point3 {
         self.origin + self.direction * t
     }
-    pub fn color(&self, rng: &mut ThreadRng, za_warudo: &dyn Hittable, depht: u16) -> Color {
+    pub fn color(&self, rng: &mut dyn RngCore, scene: &Scene, depht: u16) -> Color {
+        let lights = scene.lights();
+        let mut radiance = Color::zeros();
+        let mut throughput = Color::ones();
+        let mut ray = Ray {
+            wavelength: self.wavelength,
+            ..Ray::new(self.origin, self.direction, self.time)
+        };
+        // Density of the BSDF sample that produced `ray`, None after a delta
+        // lobe or for camera rays which light sampling cannot reproduce
+        let mut bsdf_pdf: Option<f64> = None;
+
+        for bounce in 0..depht {
+            // Object intersection
+            let mut rec = HitRecord::new();
+            if !scene.hit(&ray, 0.001, f64::INFINITY, &mut rec) {
+                radiance += throughput * scene.background().value(&ray);
+                break;
+            }
+            let material = rec.material;
+            rec.wavelength = ray.wavelength;
+
+            // Emission found by BSDF sampling, weighted against the chance
+            // the previous bounce's light sampling had to pick the same path
+            let emitted = material.emitted(&rec);
+            if emitted != Color::zeros() {
+                let weight = match bsdf_pdf {
+                    Some(pdf) if !lights.lights.is_empty() => {
+                        power_heuristic(pdf, lights.pdf_value(&ray))
+                    }
+                    _ => 1.0,
+                };
+                radiance += weight * throughput * emitted;
+            }
+
+            let srec = match material.scatter(&ray, &mut rec, rng) {
+                Option::Some(srec) => srec,
+                _ => break,
+            };
+
+            // Next event estimation: one shadow ray towards a random light
+            if !srec.is_delta && !lights.lights.is_empty() {
+                let shadow_ray = Ray::new(rec.p, lights.random(rec.p, ray.time, rng), ray.time);
+                let light_pdf = lights.pdf_value(&shadow_ray);
+                let mut light_rec = HitRecord::new();
+                if light_pdf > 0.0 && scene.hit(&shadow_ray, 0.001, f64::INFINITY, &mut light_rec) {
+                    let light = light_rec.material.emitted(&light_rec);
+                    let (wi, wo) = (shadow_ray.direction, -ray.direction);
+                    let bsdf = material.eval(&rec, wi, wo);
+                    let weight = power_heuristic(light_pdf, material.pdf(&rec, wi, wo));
+                    radiance += weight * throughput * bsdf * light / light_pdf;
+                }
+            }
+
+            throughput *= srec.attenuation;
+            bsdf_pdf = if srec.is_delta { None } else { Some(srec.pdf) };
+            ray = srec.ray;
+            // Materials do not carry the wavelength over to scattered rays
+            ray.wavelength = self.wavelength;
+
+            // Russian roulette: dim paths are terminated early, survivors are
+            // reweighted so the estimator stays unbiased
+            if bounce >= RR_MIN_BOUNCES {
+                let survival = throughput.x.max(throughput.y).max(throughput.z).min(1.0);
+                if rng.gen_range(0.0..1.0) >= survival {
+                    break;
+                }
+                throughput /= survival;
+            }
+        }
+        radiance
+    }
+}
+
+// Multiple importance sampling weight of the strategy with density `pdf_a`
+// against the one with `pdf_b`. The power heuristic (beta = 2) sharpens the
+// balance heuristic `pdf_a / (pdf_a + pdf_b)` where one strategy dominates.
+fn power_heuristic(pdf_a: f64, pdf_b: f64) -> f64 {
+    let a = pdf_a * pdf_a;
+    let b = pdf_b * pdf_b;
+    if a + b == 0.0 {
+        return 0.0;
+    }
+    a / (a + b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ray;
+    use crate::{
+        raytracer::{
+            hittable::material::{Dielectric, DiffuseLight, Lambertian, Metal},
+            AaRect, Background, HitRecord, Hittable, HittableList, LightList, Scene, Sphere,
+        },
+        vec3::{Color, Point3, Vec3},
+    };
+    use rand::{thread_rng, RngCore};
+    use std::sync::Arc;
+
+    // The original recursive estimator, without Russian roulette
+    fn color_recursive(
+        r: &Ray,
+        rng: &mut dyn RngCore,
+        za_warudo: &dyn Hittable,
+        depht: u16,
+    ) -> Color {
         if depht < 1 {
             return Color::zeros();
         }
-        // Object intersection
         let mut rec = HitRecord::new();
-        if za_warudo.hit(self, 0.001, f64::INFINITY, &mut rec) {
+        if za_warudo.hit(r, 0.001, f64::INFINITY, &mut rec) {
             let material = rec.material;
-            match material.scatter(self, &mut rec, rng) {
-                Option::Some((attenuation, new_ray)) => {
-                    return attenuation * new_ray.color(rng, za_warudo, depht - 1)
+            return match material.scatter(r, &mut rec, rng) {
+                Some(srec) => {
+                    srec.attenuation * color_recursive(&srec.ray, rng, za_warudo, depht - 1)
                 }
-                _ => return Color::zeros(),
+                None => Color::zeros(),
+            };
+        }
+        Background::sky().value(r)
+    }
+
+    fn mean_and_variance(samples: &[Color]) -> (Color, Color) {
+        let n = samples.len() as f64;
+        let mean = samples.iter().fold(Color::zeros(), |acc, &c| acc + c) / n;
+        let variance = samples.iter().fold(Color::zeros(), |acc, &c| {
+            let d = c - mean;
+            acc + d * d
+        }) / (n - 1.0);
+        (mean, variance)
+    }
+
+    #[test]
+    fn russian_roulette_is_unbiased() {
+        let mut za_warudo = HittableList::new();
+        za_warudo.add(Arc::new(Sphere::new(
+            Point3::new(0., -100.5, -1.),
+            100.,
+            Lambertian::new(Color::new(0.8, 0.8, 0.0)),
+        )));
+        za_warudo.add(Arc::new(Sphere::new(
+            Point3::new(0., 0., -1.),
+            0.5,
+            Lambertian::new(Color::new(0.7, 0.3, 0.3)),
+        )));
+        za_warudo.add(Arc::new(Sphere::new(
+            Point3::new(-1., 0., -1.),
+            0.5,
+            Dielectric::new(1.5),
+        )));
+        za_warudo.add(Arc::new(Sphere::new(
+            Point3::new(1., 0., -1.),
+            0.5,
+            Metal::new(Color::new(0.8, 0.6, 0.2), 0.3),
+        )));
+
+        let mut rng = thread_rng();
+        let scene = Scene::new(za_warudo.clone(), (0.0, 0.0), &mut rng);
+        let n = 20_000;
+        for direction in [
+            Vec3::new(0., -0.1, -1.),
+            Vec3::new(-0.9, 0., -1.),
+            Vec3::new(0.0, -1.0, -0.3),
+        ] {
+            let r = Ray::new(Point3::zeros(), direction, 0.0);
+            let iterative: Vec<Color> = (0..n).map(|_| r.color(&mut rng, &scene, 16)).collect();
+            let recursive: Vec<Color> = (0..n)
+                .map(|_| color_recursive(&r, &mut rng, &za_warudo, 16))
+                .collect();
+
+            let (mean_it, var_it) = mean_and_variance(&iterative);
+            let (mean_rec, var_rec) = mean_and_variance(&recursive);
+            for a in 0..3 {
+                let std_err = ((var_it[a] + var_rec[a]) / n as f64).sqrt();
+                assert!(
+                    (mean_it[a] - mean_rec[a]).abs() <= 5.0 * std_err + 1e-9,
+                    "channel {}: {} vs {} (std err {})",
+                    a,
+                    mean_it[a],
+                    mean_rec[a],
+                    std_err
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn multiple_importance_sampling_is_unbiased() {
+        let sphere_light = Arc::new(Sphere::new(
+            Point3::new(1., 1.5, -1.),
+            0.5,
+            DiffuseLight::new(Color::new(4., 4., 4.)),
+        ));
+        let rect_light = Arc::new(AaRect::xz(
+            -2.,
+            -1.,
+            -2.,
+            -1.,
+            2.,
+            DiffuseLight::new(Color::new(4., 2., 1.)),
+        ));
+        let mut za_warudo = HittableList::new();
+        za_warudo.add(Arc::new(Sphere::new(
+            Point3::new(0., -100.5, -1.),
+            100.,
+            Lambertian::new(Color::new(0.8, 0.8, 0.8)),
+        )));
+        za_warudo.add(Arc::new(Sphere::new(
+            Point3::new(0., 0., -1.),
+            0.5,
+            Metal::new(Color::new(0.8, 0.6, 0.2), 0.2),
+        )));
+        za_warudo.add(sphere_light.clone());
+        za_warudo.add(rect_light.clone());
+        let mut lights = LightList::new();
+        lights.add(sphere_light);
+        lights.add(rect_light);
+
+        let mut rng = thread_rng();
+        let unlit = Scene::new(za_warudo, (0.0, 0.0), &mut rng);
+        let lit = Scene::new(unlit.objects().clone(), (0.0, 0.0), &mut rng).with_lights(lights);
+        let n = 50_000;
+        for direction in [Vec3::new(0.5, -0.3, -1.), Vec3::new(0.1, 0.1, -1.)] {
+            let r = Ray::new(Point3::zeros(), direction, 0.0);
+            let sampled: Vec<Color> = (0..n).map(|_| r.color(&mut rng, &lit, 8)).collect();
+            let unsampled: Vec<Color> = (0..n).map(|_| r.color(&mut rng, &unlit, 8)).collect();
+
+            let (mean_nee, var_nee) = mean_and_variance(&sampled);
+            let (mean_bsdf, var_bsdf) = mean_and_variance(&unsampled);
+            for a in 0..3 {
+                let std_err = ((var_nee[a] + var_bsdf[a]) / n as f64).sqrt();
+                assert!(
+                    (mean_nee[a] - mean_bsdf[a]).abs() <= 5.0 * std_err + 1e-9,
+                    "channel {}: {} vs {} (std err {})",
+                    a,
+                    mean_nee[a],
+                    mean_bsdf[a],
+                    std_err
+                );
             }
         }
-        // Background
-        let norm_dir = self.direction.normalize();
-        let t = 0.5 * (norm_dir.y + 1.);
-        (1.0 - t) * Color::new(1., 1., 1.) + t * Color::new(0.5, 0.7, 1.)
     }
 }
@@ -0,0 +1,147 @@
+use super::{BvhNode, HitRecord, Hittable, HittableList, LightList, Ray};
+use crate::vec3::Color;
+use rand::RngCore;
+
+// Light coming from rays escaping the scene
+#[derive(Debug, Copy, Clone)]
+pub enum Background {
+    Solid(Color),
+    // Blend from `bottom`, straight down, to `top`, straight up
+    Gradient { bottom: Color, top: Color },
+}
+
+impl Background {
+    // White to light blue sky of the books
+    pub fn sky() -> Self {
+        Background::Gradient {
+            bottom: Color::ones(),
+            top: Color::new(0.5, 0.7, 1.0),
+        }
+    }
+    pub fn value(&self, r: &Ray) -> Color {
+        match *self {
+            Background::Solid(color) => color,
+            Background::Gradient { bottom, top } => {
+                let t = 0.5 * (r.direction.normalize().y + 1.0);
+                (1.0 - t) * bottom + t * top
+            }
+        }
+    }
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Self::sky()
+    }
+}
+
+impl From<Color> for Background {
+    fn from(color: Color) -> Self {
+        Background::Solid(color)
+    }
+}
+
+// Everything rendered by the camera: the objects with their hierarchy, the
+// lights sampled for direct lighting, the background, and the time frame
+// the hierarchy covers
+pub struct Scene {
+    objects: HittableList,
+    // None when there are no objects to build it over
+    bvh: Option<BvhNode>,
+    lights: LightList,
+    background: Background,
+    time_frame: (f64, f64),
+}
+
+impl Scene {
+    pub fn new(objects: HittableList, time_frame: (f64, f64), rng: &mut dyn RngCore) -> Self {
+        let bvh = (!objects.objects.is_empty())
+            .then(|| BvhNode::new(&objects.objects, rng, 0, objects.objects.len(), &time_frame));
+        Scene {
+            objects,
+            bvh,
+            lights: LightList::new(),
+            background: Background::default(),
+            time_frame,
+        }
+    }
+    // Lights should also be among the objects to be seen
+    pub fn with_lights(mut self, lights: LightList) -> Self {
+        self.lights = lights;
+        self
+    }
+    pub fn with_background(mut self, background: impl Into<Background>) -> Self {
+        self.background = background.into();
+        self
+    }
+
+    pub fn objects(&self) -> &HittableList {
+        &self.objects
+    }
+    pub fn lights(&self) -> &LightList {
+        &self.lights
+    }
+    pub fn background(&self) -> Background {
+        self.background
+    }
+    pub fn time_frame(&self) -> (f64, f64) {
+        self.time_frame
+    }
+
+    pub fn hit<'a>(&'a self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord<'a>) -> bool {
+        match &self.bvh {
+            Some(bvh) => bvh.hit(r, t_min, t_max, rec),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Background, Scene};
+    use crate::{
+        raytracer::{hittable::material::Material, HitRecord, Hittable, HittableList, Ray, Sphere},
+        vec3::{Color, Point3, Vec3},
+    };
+    use rand::{thread_rng, Rng};
+    use std::sync::Arc;
+
+    #[test]
+    fn backgrounds() {
+        let up = Ray::new(Point3::zeros(), Vec3::up(), 0.0);
+        let down = Ray::new(Point3::zeros(), -Vec3::up(), 0.0);
+        assert_eq!(Background::sky().value(&up), Color::new(0.5, 0.7, 1.0));
+        assert_eq!(Background::sky().value(&down), Color::ones());
+        let black = Background::from(Color::zeros());
+        assert_eq!(black.value(&up), Color::zeros());
+    }
+
+    #[test]
+    fn hits_like_its_objects() {
+        let mut rng = thread_rng();
+        let mut objects = HittableList::new();
+        for _ in 0..50 {
+            let center = Vec3::rand(&mut rng, -5.0, 5.0);
+            objects.add(Arc::new(Sphere::new(center, 0.5, Material::None)));
+        }
+        let scene = Scene::new(objects, (0.0, 1.0), &mut rng);
+        for _ in 0..1000 {
+            let r = Ray::new(
+                Point3::zeros(),
+                Vec3::rand_unit(&mut rng),
+                rng.gen_range(0.0..1.0),
+            );
+            let mut expected = HitRecord::new();
+            let mut rec = HitRecord::new();
+            assert_eq!(
+                scene.objects().hit(&r, 0.001, f64::INFINITY, &mut expected),
+                scene.hit(&r, 0.001, f64::INFINITY, &mut rec)
+            );
+            assert_eq!(rec.t, expected.t);
+        }
+
+        let empty = Scene::new(HittableList::new(), (0.0, 1.0), &mut rng);
+        let r = Ray::new(Point3::zeros(), Vec3::up(), 0.0);
+        assert!(!empty.hit(&r, 0.001, f64::INFINITY, &mut HitRecord::new()));
+    }
+}
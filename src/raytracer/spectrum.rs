@@ -0,0 +1,146 @@
+use crate::vec3::{Color, Vec3};
+use rand::{Rng, RngCore};
+use std::sync::OnceLock;
+
+// Visible range wavelengths are sampled from, in nanometers
+pub const LAMBDA_MIN: f64 = 380.0;
+pub const LAMBDA_MAX: f64 = 720.0;
+
+// Steps used to integrate the sRGB response over the visible range
+const INTEGRATION_STEPS: u32 = 1000;
+// Wavelengths used to turn spectral quantities into RGB ones
+const RGB_STEPS: u16 = 32;
+
+// Piecewise gaussian with a different width on each side of its peak
+fn gaussian(lambda: f64, mu: f64, sigma_left: f64, sigma_right: f64) -> f64 {
+    let sigma = if lambda < mu { sigma_left } else { sigma_right };
+    let t = (lambda - mu) / sigma;
+    (-0.5 * t * t).exp()
+}
+
+// CIE 1931 2° color matching functions, using the multi-lobe fit from
+// Wyman, Sloan & Shirley, "Simple Analytic Approximations to the CIE XYZ
+// Color Matching Functions" (JCGT 2013)
+pub fn cie_xyz(lambda: f64) -> Vec3 {
+    Vec3::new(
+        1.056 * gaussian(lambda, 599.8, 37.9, 31.0) + 0.362 * gaussian(lambda, 442.0, 16.0, 26.7)
+            - 0.065 * gaussian(lambda, 501.1, 20.4, 26.2),
+        0.821 * gaussian(lambda, 568.8, 46.9, 40.5) + 0.286 * gaussian(lambda, 530.9, 16.3, 31.1),
+        1.217 * gaussian(lambda, 437.0, 11.8, 36.0) + 0.681 * gaussian(lambda, 459.0, 26.0, 13.8),
+    )
+}
+
+// CIE XYZ to linear sRGB (D65 white point)
+pub fn xyz_to_srgb(xyz: Vec3) -> Color {
+    Color::new(
+        3.2404542 * xyz.x - 1.5371385 * xyz.y - 0.4985314 * xyz.z,
+        -0.9692660 * xyz.x + 1.8760108 * xyz.y + 0.0415560 * xyz.z,
+        0.0556434 * xyz.x - 0.2040259 * xyz.y + 1.0572252 * xyz.z,
+    )
+}
+
+// sRGB color of a single wavelength, out of gamut negative parts clamped
+fn srgb_response(lambda: f64) -> Color {
+    let rgb = xyz_to_srgb(cie_xyz(lambda));
+    Color::new(rgb.x.max(0.0), rgb.y.max(0.0), rgb.z.max(0.0))
+}
+
+// Integral of `srgb_response` over the visible range, per channel
+fn response_integral() -> Color {
+    static INTEGRAL: OnceLock<Color> = OnceLock::new();
+    *INTEGRAL.get_or_init(|| {
+        let step = (LAMBDA_MAX - LAMBDA_MIN) / INTEGRATION_STEPS as f64;
+        let mut integral = Color::zeros();
+        for i in 0..INTEGRATION_STEPS {
+            integral += step * srgb_response(LAMBDA_MIN + (i as f64 + 0.5) * step);
+        }
+        integral
+    })
+}
+
+// Uniform wavelength in the `stratum`-th of `strata` equal slices of the
+// visible range, so the samples of a pixel cover the whole spectrum
+pub fn sample_wavelength(stratum: u16, strata: u16, rng: &mut dyn RngCore) -> f64 {
+    let offset = (stratum as f64 + rng.gen_range(0.0..1.0)) / strata as f64;
+    LAMBDA_MIN + offset * (LAMBDA_MAX - LAMBDA_MIN)
+}
+
+// Weight turning the radiance carried at `lambda` into its sRGB contribution,
+// divided by the density of `sample_wavelength`. It is normalized so that its
+// expected value is white, which keeps spectral and RGB renders of a
+// dispersion-free scene identical on average.
+pub fn wavelength_weight(lambda: f64) -> Color {
+    let integral = response_integral();
+    let response = srgb_response(lambda) * (LAMBDA_MAX - LAMBDA_MIN);
+    Color::new(
+        response.x / integral.x,
+        response.y / integral.y,
+        response.z / integral.z,
+    )
+}
+
+// sRGB color of a light whose spectral distribution relative to white is
+// `f`, integrated over stratified wavelengths
+pub fn to_rgb(f: impl Fn(f64) -> f64) -> Color {
+    static WEIGHTS: OnceLock<Vec<(f64, Color)>> = OnceLock::new();
+    let weights = WEIGHTS.get_or_init(|| {
+        let step = (LAMBDA_MAX - LAMBDA_MIN) / RGB_STEPS as f64;
+        (0..RGB_STEPS)
+            .map(|i| {
+                let lambda = LAMBDA_MIN + (i as f64 + 0.5) * step;
+                (lambda, wavelength_weight(lambda) / RGB_STEPS as f64)
+            })
+            .collect()
+    });
+    let mut rgb = Color::zeros();
+    for &(lambda, weight) in weights {
+        rgb += f(lambda) * weight;
+    }
+    rgb
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cie_xyz, sample_wavelength, to_rgb, wavelength_weight, xyz_to_srgb};
+    use crate::vec3::{Color, Vec3};
+    use rand::thread_rng;
+
+    #[test]
+    fn color_matching_peaks() {
+        // Y peaks in the green, Z in the blue, X has its main lobe in the red
+        assert!(cie_xyz(555.).y > 0.99);
+        assert!(cie_xyz(555.).y > cie_xyz(500.).y);
+        assert!(cie_xyz(445.).z > cie_xyz(445.).x);
+        assert!(cie_xyz(600.).x > cie_xyz(600.).y);
+    }
+
+    #[test]
+    fn white_point() {
+        // D65 white maps to sRGB white
+        let d65 = Vec3::new(0.95047, 1.0, 1.08883);
+        assert!((xyz_to_srgb(d65) - Color::ones()).length() < 1e-3);
+    }
+
+    #[test]
+    fn weights_average_to_white() {
+        let mut rng = thread_rng();
+        let n: u32 = 200_000;
+        let mut sum = Color::zeros();
+        for i in 0..n {
+            sum += wavelength_weight(sample_wavelength((i % 16) as u16, 16, &mut rng));
+        }
+        let mean = sum / n as f64;
+        assert!((mean - Color::ones()).length() < 0.02, "mean {:?}", mean);
+
+        assert!(wavelength_weight(450.).z > wavelength_weight(450.).x);
+        assert!(wavelength_weight(650.).x > wavelength_weight(650.).z);
+    }
+
+    #[test]
+    fn flat_spectrum_is_white() {
+        assert!((to_rgb(|_| 0.5) - 0.5 * Color::ones()).length() < 0.01);
+        // Only long wavelengths give red
+        let red = to_rgb(|lambda| if lambda > 600. { 1.0 } else { 0.0 });
+        assert!(red.x > 0.5 && red.y < 0.2 && red.z < 0.05, "{:?}", red);
+    }
+}
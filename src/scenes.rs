@@ -0,0 +1,787 @@
+// Example scenes, built on the public API only
+use another_rust_ray_tracing_in_one_weekend::{
+    raytracer::{
+        hittable::material::{
+            Coated, Conductor, Cutout, Dielectric, DiffuseLight, Hair, Ior, Lambertian, Material,
+            Metal, Principled, RoughDielectric, Scatter, ScatterRecord,
+        },
+        hittable::texture::{noise::noise, Texture},
+        AaRect, Animated, Camera, Cone, Csg, Curve, Curves, Cylinder, Disk, Heightfield, HitRecord,
+        Hittable, HittableList, LightList, Mesh, MotionPath, Perturbed, Plane, Quad, Ray,
+        RayMarched, Scene, Sdf, Sided, Sphere, Torus, Transform, TriangleMesh,
+    },
+    vec3::{Color, Onb, Point3, Vec3},
+};
+use rand::{thread_rng, Rng, RngCore};
+use std::{f64::consts::PI, sync::Arc};
+
+// Shutter open over [0, 1], the speed of moving objects being per unit time
+const TIME_FRAME: (f64, f64) = (0.0, 1.0);
+
+pub fn weekend_scene() -> (Camera, Scene) {
+    let lookfrom = Point3::new(13., 2., 3.);
+    let lookat = Point3::new(0., 0., 0.);
+    let dist_to_focus = (lookfrom - lookat).length();
+
+    let cam = Camera::new(
+        lookfrom,
+        lookat,
+        Vec3::up(),
+        16. / 9.,
+        240,
+        20.,
+        0.1,
+        dist_to_focus,
+    );
+
+    let mat_lambertian = Lambertian::new(Color::new(0.8, 0.0, 0.8));
+    let mat_dielectric = Dielectric::new(1.5);
+    let mat_metal = Metal::new(Color::new(0.7, 0.6, 0.5), 0.01);
+
+    // Rolling hills, rising into mountains away from the spheres
+    let ground = Heightfield::from_fn(
+        Point3::new(-100., 0., -100.),
+        (200., 200.),
+        (401, 401),
+        |x, z| {
+            let hills = 0.6 * noise(Point3::new(0.15 * x, 0.5, 0.15 * z))
+                + 0.15 * noise(Point3::new(0.6 * x, 1.5, 0.6 * z));
+            let distance = (x * x + z * z).sqrt();
+            let rise = ((distance - 20.0) / 60.0).clamp(0.0, 1.0).powi(2);
+            hills + 12.0 * rise * (0.6 + 0.4 * noise(Point3::new(0.04 * x, 2.5, 0.04 * z)))
+        },
+        Lambertian::new(Color::new(0.5, 0.5, 0.4)),
+    );
+    let on_ground = |x: f64, z: f64, radius: f64| {
+        Point3::new(x, ground.height_at(x, z).unwrap_or(0.0) + radius, z)
+    };
+
+    let mut world = random_scene(&ground);
+    world.add(Arc::new(Sphere::new(
+        on_ground(-4., 0., 1.0),
+        1.0,
+        mat_lambertian,
+    )));
+    world.add(Arc::new(Sphere::new(
+        on_ground(0., 0., 1.0),
+        1.0,
+        mat_dielectric,
+    )));
+    world.add(Arc::new(Sphere::new(
+        on_ground(4., 0., 1.0),
+        1.0,
+        mat_metal,
+    )));
+    world.add(Arc::new(ground));
+
+    (cam, Scene::new(world, TIME_FRAME, &mut thread_rng()))
+}
+
+// Closed Cornell box, lit by a small ceiling panel and a small sphere
+pub fn cornell_box() -> (Camera, Scene) {
+    let lookfrom = Point3::new(278., 278., -1000.);
+    let lookat = Point3::new(278., 278., 0.);
+
+    let cam = Camera::new(
+        lookfrom,
+        lookat,
+        Vec3::up(),
+        1.,
+        240,
+        40.,
+        0.0,
+        (lookfrom - lookat).length(),
+    );
+
+    let red = Lambertian::new(Color::new(0.65, 0.05, 0.05));
+    let white = Lambertian::new(Color::new(0.73, 0.73, 0.73));
+    let green = Lambertian::new(Color::new(0.12, 0.45, 0.15));
+    let panel = Arc::new(AaRect::xz(
+        213.,
+        343.,
+        227.,
+        332.,
+        554.,
+        DiffuseLight::new(Color::new(15., 15., 15.)),
+    ));
+    let bulb = Arc::new(Sphere::new(
+        Point3::new(400., 60., 150.),
+        20.,
+        DiffuseLight::new(Color::new(20., 16., 8.)),
+    ));
+
+    let mut world = HittableList::new();
+    world.add(Arc::new(AaRect::yz(0., 555., -800., 555., 555., green)));
+    world.add(Arc::new(AaRect::yz(0., 555., -800., 555., 0., red)));
+    world.add(Arc::new(AaRect::xz(
+        0.,
+        555.,
+        -800.,
+        555.,
+        0.,
+        white.clone(),
+    )));
+    world.add(Arc::new(AaRect::xz(
+        0.,
+        555.,
+        -800.,
+        555.,
+        555.,
+        white.clone(),
+    )));
+    world.add(Arc::new(AaRect::xy(
+        0.,
+        555.,
+        0.,
+        555.,
+        555.,
+        white.clone(),
+    )));
+    // The camera sees through the back of the front wall, which still
+    // bounces light back into the box
+    world.add(Arc::new(Sided::culled(Arc::new(AaRect::xy(
+        0., 555., 0., 555., -800., white,
+    )))));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(190., 90., 190.),
+        90.,
+        // Green bottle glass, tinted over the whole diameter
+        Dielectric::tinted(1.5, Color::new(0.55, 0.85, 0.6), 1. / 180.),
+    )));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(370., 120., 350.),
+        120.,
+        Metal::new(Color::new(0.8, 0.85, 0.88), 0.0),
+    )));
+    world.add(panel.clone());
+    world.add(bulb.clone());
+
+    let mut lights = LightList::new();
+    lights.add(panel);
+    lights.add(bulb);
+
+    let scene = Scene::new(world, TIME_FRAME, &mut thread_rng())
+        .with_lights(lights)
+        .with_background(Color::zeros());
+    (cam, scene)
+}
+
+// Row of spheres showing off the physically based materials
+pub fn materials_scene() -> (Camera, Scene) {
+    let lookfrom = Point3::new(0., 3., 12.);
+    let lookat = Point3::new(0., 0.8, 0.);
+
+    let cam = Camera::new(
+        lookfrom,
+        lookat,
+        Vec3::up(),
+        16. / 9.,
+        240,
+        30.,
+        0.0,
+        (lookfrom - lookat).length(),
+    );
+
+    let panel = Arc::new(AaRect::xz(
+        -3.,
+        3.,
+        -2.,
+        2.,
+        6.,
+        DiffuseLight::new(Color::new(4., 4., 4.)),
+    ));
+
+    let mut world = HittableList::new();
+    // Polished tiles alternating with rough ones
+    let floor = Principled::builder(Texture::checker(
+        Color::new(0.6, 0.6, 0.6),
+        Color::new(0.2, 0.2, 0.25),
+        1.,
+    ))
+    .roughness(Texture::checker(
+        Color::new(0.1, 0.1, 0.1),
+        Color::ones(),
+        1.,
+    ));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0., -1000., 0.),
+        1000.,
+        floor.build(),
+    )));
+    let front_row: [Material; 5] = [
+        // Car paint: metallic flakes under a thick varnish
+        Coated::new(
+            Principled::builder(Color::new(0.05, 0.2, 0.05))
+                .metallic(0.6)
+                .roughness(0.4)
+                .build(),
+            1.5,
+        ),
+        Principled::builder(Color::new(0.7, 0.05, 0.05))
+            .metallic(0.3)
+            .specular(0.8)
+            .clearcoat(1.0, 0.05)
+            .build(),
+        Principled::builder(Color::new(0.2, 0.3, 0.7))
+            .sheen(1.0)
+            .roughness(0.9)
+            .build(),
+        Principled::builder(Color::new(0.9, 1.0, 0.95))
+            .roughness(0.1)
+            .transmission(1.0, 1.5)
+            .build(),
+        // Soap bubble: a film of water with nothing inside
+        Coated::thin_film(Dielectric::new(1.0), 1.33, 350.),
+    ];
+    for (i, material) in front_row.into_iter().enumerate() {
+        world.add(Arc::new(Sphere::new(
+            Point3::new(-2.8 + 1.4 * i as f64, 0.5, 2.5),
+            0.5,
+            material,
+        )));
+    }
+    let materials = [
+        Conductor::gold(0.3),
+        Conductor::copper(0.15),
+        Conductor::aluminium(0.5),
+        RoughDielectric::new(1.5, 0.3),
+    ];
+    let mut spheres: Vec<Arc<dyn Hittable + Send + Sync>> = materials
+        .into_iter()
+        .enumerate()
+        .map(|(i, material)| {
+            Arc::new(Sphere::new(
+                Point3::new(-3.3 + 2.2 * i as f64, 1., 0.),
+                1.,
+                material,
+            )) as Arc<dyn Hittable + Send + Sync>
+        })
+        .collect();
+    // Hammered gold and tiles of aluminium tilted every other way
+    spheres[0] = Arc::new(Perturbed::bump(
+        spheres[0].clone(),
+        Texture::noise(4.),
+        0.02,
+    ));
+    spheres[2] = Arc::new(Perturbed::normal_map(
+        spheres[2].clone(),
+        Texture::checker(Color::new(0.7, 0.5, 0.95), Color::new(0.3, 0.5, 0.95), 0.3),
+    ));
+    for sphere in spheres {
+        world.add(sphere);
+    }
+    // Lattice fence behind the spheres, only painted on the camera side
+    let fence = Cutout::new(
+        Material::custom(OrenNayar::new(Color::new(0.45, 0.3, 0.15), 0.5)),
+        Texture::checker(Color::ones(), Color::zeros(), 0.25),
+    );
+    world.add(Arc::new(Sided::one_sided(Arc::new(AaRect::xy(
+        -8., 8., 0., 2.5, -2.6, fence,
+    )))));
+    world.add(panel.clone());
+
+    let mut lights = LightList::new();
+    lights.add(panel);
+
+    (
+        cam,
+        Scene::new(world, TIME_FRAME, &mut thread_rng()).with_lights(lights),
+    )
+}
+
+// Glass spheres over a black and white floor, whose edges get rainbow
+// fringes once seen through a dispersive material
+pub fn dispersion_scene() -> (Camera, Scene) {
+    let lookfrom = Point3::new(0., 2., 10.);
+    let lookat = Point3::new(0., 1., 0.);
+
+    let mut cam = Camera::new(
+        lookfrom,
+        lookat,
+        Vec3::up(),
+        16. / 9.,
+        240,
+        30.,
+        0.0,
+        (lookfrom - lookat).length(),
+    );
+    cam.set_spectral(true);
+
+    let panel = Arc::new(AaRect::xz(
+        -2.,
+        2.,
+        -1.,
+        1.,
+        6.,
+        DiffuseLight::new(Color::new(6., 6., 6.)),
+    ));
+
+    let mut world = HittableList::new();
+    let floor = Principled::builder(Texture::checker(
+        Color::new(0.9, 0.9, 0.9),
+        Color::new(0.02, 0.02, 0.02),
+        0.5,
+    ))
+    .specular(0.0);
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0., -1000., 0.),
+        1000.,
+        floor.build(),
+    )));
+    let glasses = [
+        Ior::bk7(),
+        Ior::dense_flint(),
+        // Far more dispersive than any real glass
+        Ior::cauchy(1.45, 0.06),
+    ];
+    for (i, ior) in glasses.into_iter().enumerate() {
+        world.add(Arc::new(Sphere::new(
+            Point3::new(-2.3 + 2.3 * i as f64, 1., 0.),
+            1.,
+            Dielectric::new(ior),
+        )));
+    }
+    world.add(panel.clone());
+
+    let mut lights = LightList::new();
+    lights.add(panel);
+
+    (
+        cam,
+        Scene::new(world, TIME_FRAME, &mut thread_rng()).with_lights(lights),
+    )
+}
+
+// Analytic shapes standing on an infinite plane, lit by a disk, with a
+// tilted box behind them and a sphere missing an octant in front
+pub fn primitives_scene() -> (Camera, Scene) {
+    let lookfrom = Point3::new(0., 4., 12.);
+    let lookat = Point3::new(0., 1., 0.);
+
+    let cam = Camera::new(
+        lookfrom,
+        lookat,
+        Vec3::up(),
+        16. / 9.,
+        240,
+        30.,
+        0.0,
+        (lookfrom - lookat).length(),
+    );
+
+    let lamp = Arc::new(Disk::new(
+        Point3::new(0., 7., 2.),
+        -Vec3::up(),
+        2.,
+        DiffuseLight::new(Color::new(5., 5., 5.)),
+    ));
+
+    let mut world = HittableList::new();
+    world.add(Arc::new(Plane::new(
+        Point3::zeros(),
+        Vec3::up(),
+        Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+    )));
+    world.add(Arc::new(Cylinder::new(
+        Point3::new(-3.5, 0., 0.),
+        2. * Vec3::up(),
+        0.8,
+        Lambertian::new(Color::new(0.7, 0.2, 0.2)),
+    )));
+    world.add(Arc::new(Cone::new(
+        Point3::new(-1.2, 0., 0.),
+        2.2 * Vec3::up(),
+        0.9,
+        Conductor::copper(0.2),
+    )));
+    // Spinning on itself while rising, blurred over the shutter time
+    let torus = Arc::new(Torus::new(
+        Point3::zeros(),
+        Vec3::new(0., 1., 1.),
+        0.8,
+        0.3,
+        Lambertian::new(Color::new(0.2, 0.3, 0.7)),
+    ));
+    let position = Transform::translation(Point3::new(1.4, 0.9, 0.));
+    world.add(Arc::new(Animated::new(
+        torus,
+        vec![
+            (TIME_FRAME.0, position),
+            (
+                TIME_FRAME.1,
+                Transform::rotation(Vec3::up(), 30.).then_translate(Point3::new(1.4, 1.1, 0.)),
+            ),
+        ],
+    )));
+    world.add(Arc::new(Disk::new(
+        Point3::new(3.6, 1., -0.5),
+        Vec3::new(-0.4, 0.2, 1.),
+        1.,
+        Conductor::gold(0.05),
+    )));
+    world.add(Arc::new(Quad::cuboid(
+        Point3::new(-1., 0., -3.),
+        Vec3::new(1.6, 0., -0.8),
+        Vec3::new(0., 2.5, 0.),
+        Vec3::new(0.4, 0., 0.8),
+        Lambertian::new(Color::new(0.8, 0.7, 0.3)),
+    )));
+    let center = Point3::new(0.2, 0.7, 2.5);
+    world.add(Arc::new(Csg::difference(
+        Arc::new(Sphere::new(
+            center,
+            0.7,
+            Lambertian::new(Color::new(0.9, 0.9, 0.9)),
+        )),
+        Arc::new(Quad::cuboid(
+            center,
+            Vec3::new(1., 0., 0.),
+            Vec3::new(0., 1., 0.),
+            Vec3::new(0., 0., 1.),
+            Lambertian::new(Color::new(0.9, 0.4, 0.1)),
+        )),
+    )));
+    world.add(Arc::new(RayMarched::new(
+        Sdf::smooth_union(
+            Sdf::sphere(Point3::new(2.1, 0.5, 2.3), 0.5),
+            Sdf::cuboid(Point3::new(2.8, 0.35, 2.1), Vec3::new(0.35, 0.35, 0.35)),
+            0.3,
+        ),
+        Conductor::copper(0.05),
+    )));
+    world.add(lamp.clone());
+
+    let mut lights = LightList::new();
+    lights.add(lamp);
+
+    (
+        cam,
+        Scene::new(world, TIME_FRAME, &mut thread_rng()).with_lights(lights),
+    )
+}
+
+// Coarse cube as a modeling tool would export it
+const CUBE_OBJ: &str = "# cube
+v -1 -1 -1
+v 1 -1 -1
+v -1 1 -1
+v 1 1 -1
+v -1 -1 1
+v 1 -1 1
+v -1 1 1
+v 1 1 1
+f 1 3 4 2
+f 5 6 8 7
+f 1 2 6 5
+f 3 7 8 4
+f 1 5 7 3
+f 2 4 8 6
+";
+
+// Low-poly cubes and octahedra, raw then subdivided once and thrice
+pub fn mesh_scene() -> (Camera, Scene) {
+    let lookfrom = Point3::new(0., 5., 12.);
+    let lookat = Point3::new(0., 1.2, 0.);
+
+    let cam = Camera::new(
+        lookfrom,
+        lookat,
+        Vec3::up(),
+        16. / 9.,
+        240,
+        35.,
+        0.0,
+        (lookfrom - lookat).length(),
+    );
+
+    let cube = Mesh::from_obj(CUBE_OBJ).expect("Invalid cube");
+    let octahedron = Mesh::new(
+        vec![
+            Point3::new(1., 0., 0.),
+            Point3::new(-1., 0., 0.),
+            Point3::new(0., 1., 0.),
+            Point3::new(0., -1., 0.),
+            Point3::new(0., 0., 1.),
+            Point3::new(0., 0., -1.),
+        ],
+        vec![
+            vec![0, 2, 4],
+            vec![2, 1, 4],
+            vec![1, 3, 4],
+            vec![3, 0, 4],
+            vec![2, 0, 5],
+            vec![1, 2, 5],
+            vec![3, 1, 5],
+            vec![0, 3, 5],
+        ],
+    );
+    let placed = |mesh: &Mesh, center: Point3, scale: f64| {
+        let mut mesh = mesh.clone();
+        for p in mesh.positions.iter_mut() {
+            *p = center + scale * *p;
+        }
+        mesh
+    };
+
+    let mut rng = thread_rng();
+    let mut world = HittableList::new();
+    world.add(Arc::new(Plane::new(
+        Point3::zeros(),
+        Vec3::up(),
+        Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+    )));
+    for (i, levels) in [0, 1, 3].into_iter().enumerate() {
+        let x = 3.2 * (i as f64 - 1.0);
+        world.add(Arc::new(TriangleMesh::new(
+            &placed(&cube, Point3::new(x, 0.8, -1.5), 0.8).subdivide(levels),
+            Lambertian::new(Color::new(0.8, 0.3, 0.2)),
+            &mut rng,
+        )));
+        world.add(Arc::new(TriangleMesh::new(
+            &placed(&octahedron, Point3::new(x, 1.0, 1.5), 1.0).subdivide(levels),
+            Metal::new(Color::new(0.8, 0.8, 0.9), 0.05),
+            &mut rng,
+        )));
+    }
+
+    (cam, Scene::new(world, TIME_FRAME, &mut rng))
+}
+
+// Furry ball, its strands bending down under their weight
+pub fn hair_scene() -> (Camera, Scene) {
+    let lookfrom = Point3::new(0., 2., 6.);
+    let lookat = Point3::new(0., 1., 0.);
+
+    let cam = Camera::new(
+        lookfrom,
+        lookat,
+        Vec3::up(),
+        16. / 9.,
+        240,
+        30.,
+        0.0,
+        (lookfrom - lookat).length(),
+    );
+
+    let lamp = Arc::new(Disk::new(
+        Point3::new(-3., 6., 4.),
+        Vec3::new(3., -6., -4.),
+        1.5,
+        DiffuseLight::new(Color::new(6., 6., 6.)),
+    ));
+
+    let center = Point3::new(0., 1., 0.);
+    let (radius, length) = (0.8, 0.35);
+    let mut rng = thread_rng();
+    let strands: Vec<Curve> = (0..20_000)
+        .map(|_| {
+            let normal = Vec3::rand_unit(&mut rng);
+            let root = center + radius * normal;
+            let direction = (normal + 0.3 * Vec3::rand_unit(&mut rng)).normalize();
+            let sag = -0.4 * length * Vec3::up();
+            Curve::new(
+                [
+                    root,
+                    root + length / 3.0 * direction,
+                    root + 2.0 * length / 3.0 * direction + 0.3 * sag,
+                    root + length * direction + sag,
+                ],
+                0.012,
+                0.002,
+            )
+        })
+        .collect();
+
+    let mut world = HittableList::new();
+    world.add(Arc::new(Plane::new(
+        Point3::zeros(),
+        Vec3::up(),
+        Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+    )));
+    world.add(Arc::new(Sphere::new(
+        center,
+        radius,
+        Lambertian::new(Color::new(0.3, 0.15, 0.05)),
+    )));
+    world.add(Arc::new(Curves::new(
+        &strands,
+        Hair::new(Color::new(0.6, 0.3, 0.1), Color::new(0.3, 0.3, 0.3), 60.0),
+        &mut rng,
+    )));
+    world.add(lamp.clone());
+
+    let mut lights = LightList::new();
+    lights.add(lamp);
+
+    (
+        cam,
+        Scene::new(world, TIME_FRAME, &mut rng).with_lights(lights),
+    )
+}
+
+// Spheres blurred along the motion paths they can follow while the shutter
+// is open: a straight line, a Bézier hop, keyframes and a swelling radius
+pub fn motion_scene() -> (Camera, Scene) {
+    let lookfrom = Point3::new(0., 3., 10.);
+    let lookat = Point3::new(0., 1., 0.);
+
+    let cam = Camera::new(
+        lookfrom,
+        lookat,
+        Vec3::up(),
+        16. / 9.,
+        240,
+        30.,
+        0.0,
+        (lookfrom - lookat).length(),
+    );
+
+    let mut world = HittableList::new();
+    world.add(Arc::new(Plane::new(
+        Point3::zeros(),
+        Vec3::up(),
+        Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+    )));
+    world.add(Arc::new(Sphere::new_moving(
+        Point3::new(-4., 0.5, 0.),
+        Vec3::up(),
+        0.5,
+        Lambertian::new(Color::new(0.7, 0.2, 0.2)),
+    )));
+    // Inner control points 4/3 as high as the top of the hop
+    let start = Point3::new(-2.5, 0.5, 0.);
+    let lift = Vec3::up() * 2.0 * 4.0 / 3.0;
+    let step = Vec3::new(2., 0., 0.);
+    world.add(Arc::new(Sphere::along(
+        MotionPath::bezier(
+            [start, start + lift, start + step + lift, start + step],
+            TIME_FRAME,
+        ),
+        0.5,
+        Lambertian::new(Color::new(0.2, 0.6, 0.2)),
+    )));
+    world.add(Arc::new(Sphere::along(
+        MotionPath::keyframes(vec![
+            (0.0, Point3::new(1., 0.5, 1.)),
+            (0.3, Point3::new(2., 1.5, 1.)),
+            (0.6, Point3::new(2., 0.5, -1.)),
+            (1.0, Point3::new(3., 1.5, -1.)),
+        ]),
+        0.5,
+        Metal::new(Color::new(0.8, 0.6, 0.2), 0.1),
+    )));
+    world.add(Arc::new(
+        Sphere::new(
+            Point3::new(4.5, 1., 0.),
+            0.5,
+            Lambertian::new(Color::new(0.2, 0.3, 0.7)),
+        )
+        .with_radii(vec![(0.0, 0.3), (0.5, 0.9), (1.0, 0.3)]),
+    ));
+
+    (cam, Scene::new(world, TIME_FRAME, &mut thread_rng()))
+}
+
+fn random_scene(ground: &Heightfield) -> HittableList {
+    let mut za_warudo = HittableList::new();
+    let mut rng = thread_rng();
+
+    for a in -11..11 {
+        for b in -11..11 {
+            let chose_mat = rng.gen_range(0..100);
+            let x = a as f64 + 0.9 * rng.gen_range(0.0..1.0);
+            let z = b as f64 + 0.9 * rng.gen_range(0.0..1.0);
+            let center = Point3::new(x, ground.height_at(x, z).unwrap_or(0.0) + 0.2, z);
+
+            match chose_mat {
+                // Diffuse
+                0..=79 => za_warudo.add(Arc::new(Sphere::new_moving(
+                    center,
+                    Vec3::up() * rng.gen_range(0.0..0.5),
+                    0.2,
+                    Lambertian::new(
+                        Color::rand(&mut rng, 0.0, 1.0) * Color::rand(&mut rng, 0.0, 1.0),
+                    ),
+                ))),
+                // Metal
+                80..=94 => za_warudo.add(Arc::new(Sphere::new(
+                    center,
+                    0.2,
+                    Metal::new(Color::rand(&mut rng, 0.5, 1.0), rng.gen_range(0.0..0.5)),
+                ))),
+                // Glass
+                _ => za_warudo.add(Arc::new(Sphere::new(center, 0.2, Dielectric::new(1.5)))),
+            }
+        }
+    }
+
+    za_warudo
+}
+
+// Rough diffuse surface (Oren-Nayar) brought in through the `Scatter` trait,
+// as any material defined outside of the ray tracer would be
+#[derive(Debug)]
+struct OrenNayar {
+    albedo: Color,
+    a: f64,
+    b: f64,
+}
+
+impl OrenNayar {
+    // `sigma` is the standard deviation of the facet angles, in radians
+    fn new(albedo: Color, sigma: f64) -> Self {
+        let sigma2 = sigma * sigma;
+        OrenNayar {
+            albedo,
+            a: 1.0 - 0.5 * sigma2 / (sigma2 + 0.33),
+            b: 0.45 * sigma2 / (sigma2 + 0.09),
+        }
+    }
+}
+
+impl Scatter for OrenNayar {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &mut HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<ScatterRecord> {
+        let direction = Onb::from_w(rec.normal).local(Vec3::rand_cosine_direction(rng));
+        let wo = -r_in.direction;
+        let pdf = self.pdf(rec, direction, wo);
+        if pdf <= 0.0 {
+            return None;
+        }
+        Some(ScatterRecord::new(
+            self.eval(rec, direction, wo) / pdf,
+            Ray::new(rec.p, direction, r_in.time),
+            pdf,
+        ))
+    }
+    fn eval(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> Color {
+        let (wi, wo) = (wi.normalize(), wo.normalize());
+        let cos_i = rec.normal.dot(wi);
+        let cos_o = rec.normal.dot(wo);
+        if cos_i <= 0.0 || cos_o <= 0.0 {
+            return Color::zeros();
+        }
+        let sin_i = (1.0 - cos_i * cos_i).max(0.0).sqrt();
+        let sin_o = (1.0 - cos_o * cos_o).max(0.0).sqrt();
+        // Cosine of the azimuth between both directions
+        let cos_phi = if sin_i > 1e-8 && sin_o > 1e-8 {
+            ((wi - cos_i * rec.normal).dot(wo - cos_o * rec.normal) / (sin_i * sin_o)).max(0.0)
+        } else {
+            0.0
+        };
+        // Sine of the largest angle to the normal, tangent of the smallest
+        let (sin_alpha, tan_beta) = if cos_i > cos_o {
+            (sin_o, sin_i / cos_i)
+        } else {
+            (sin_i, sin_o / cos_o)
+        };
+        self.albedo / PI * (self.a + self.b * cos_phi * sin_alpha * tan_beta) * cos_i
+    }
+    fn pdf(&self, rec: &HitRecord, wi: Vec3, _wo: Vec3) -> f64 {
+        rec.normal.dot(wi.normalize()).max(0.0) / PI
+    }
+}
@@ -1,4 +1,5 @@
-use rand::{rngs::ThreadRng, Rng};
+use rand::{Rng, RngCore};
+use std::f64::consts::PI;
 use std::ops;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -74,14 +75,14 @@ impl Vec3 {
         }
     }
     // Vec3 rng generation
-    pub fn rand(rng: &mut ThreadRng, min: f64, max: f64) -> Vec3 {
+    pub fn rand(rng: &mut dyn RngCore, min: f64, max: f64) -> Vec3 {
         Vec3 {
             x: rng.gen_range(min..max),
             y: rng.gen_range(min..max),
             z: rng.gen_range(min..max),
         }
     }
-    pub fn rand_in_sphere(rng: &mut ThreadRng) -> Vec3 {
+    pub fn rand_in_sphere(rng: &mut dyn RngCore) -> Vec3 {
         loop {
             let p = Vec3::rand(rng, -1., 1.);
             if p.length_squared() < 1.0 {
@@ -89,10 +90,21 @@ impl Vec3 {
             }
         }
     }
-    pub fn rand_unit(rng: &mut ThreadRng) -> Vec3 {
+    pub fn rand_unit(rng: &mut dyn RngCore) -> Vec3 {
         Self::rand_in_sphere(rng).normalize()
     }
-    pub fn rand_in_disk(rng: &mut ThreadRng) -> Vec3 {
+    // Cosine weighted direction around +z
+    pub fn rand_cosine_direction(rng: &mut dyn RngCore) -> Vec3 {
+        let r1: f64 = rng.gen_range(0.0..1.0);
+        let r2: f64 = rng.gen_range(0.0..1.0);
+        let phi = 2.0 * PI * r1;
+        Vec3 {
+            x: phi.cos() * r2.sqrt(),
+            y: phi.sin() * r2.sqrt(),
+            z: (1.0 - r2).sqrt(),
+        }
+    }
+    pub fn rand_in_disk(rng: &mut dyn RngCore) -> Vec3 {
         loop {
             let p = Vec3 {
                 x: rng.gen_range(-1.0..1.0),
@@ -105,6 +117,36 @@ impl Vec3 {
         }
     }
 }
+// Orthonormal basis, w being the "up" axis of the local frame
+#[derive(Debug, Copy, Clone)]
+pub struct Onb {
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3,
+}
+
+impl Onb {
+    pub fn from_w(n: Vec3) -> Self {
+        let w = n.normalize();
+        let a = if w.x.abs() > 0.9 {
+            Vec3::up()
+        } else {
+            Vec3::new(1., 0., 0.)
+        };
+        let v = w.cross(a).normalize();
+        let u = v.cross(w);
+        Onb { u, v, w }
+    }
+    // Local coordinates to world
+    pub fn local(&self, a: Vec3) -> Vec3 {
+        a.x * self.u + a.y * self.v + a.z * self.w
+    }
+    // World coordinates to local
+    pub fn to_local(&self, a: Vec3) -> Vec3 {
+        Vec3::new(a.dot(self.u), a.dot(self.v), a.dot(self.w))
+    }
+}
+
 // vec3 . float operations
 impl ops::Mul<f64> for Vec3 {
     type Output = Vec3;
@@ -248,6 +290,10 @@ pub type Color = Vec3;
 pub type Point3 = Vec3;
 
 impl Color {
+    // Relative luminance of linear sRGB
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.x + 0.7152 * self.y + 0.0722 * self.z
+    }
     pub fn write(&self, samples_per_pixel: u16) -> String {
         let scale = 1.0 / samples_per_pixel as f64;
 
@@ -266,7 +312,7 @@ impl Color {
 
 #[cfg(test)]
 mod tests {
-    use crate::vec3::Vec3;
+    use crate::vec3::{Onb, Vec3};
 
     #[test]
     fn normalize() {
@@ -469,4 +515,20 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn onb() {
+        for n in [Vec3::up(), Vec3::new(1., 0., 0.), Vec3::new(-0.3, 0.2, 5.)] {
+            let onb = Onb::from_w(n);
+            assert!((onb.w - n.normalize()).near_zero());
+            assert!(onb.u.dot(onb.v).abs() < 1e-12);
+            assert!(onb.u.dot(onb.w).abs() < 1e-12);
+            assert!(onb.v.dot(onb.w).abs() < 1e-12);
+            assert!((onb.u.cross(onb.v) - onb.w).near_zero());
+            assert!((onb.local(Vec3::new(0., 0., 2.)) - 2. * onb.w).near_zero());
+
+            let a = Vec3::new(0.5, -2., 3.);
+            assert!((onb.to_local(onb.local(a)) - a).near_zero());
+        }
+    }
 }